@@ -62,6 +62,26 @@ impl PerlinNoiseGenerator {
 
         accum.abs()
     }
+
+    /// Fractional Brownian motion: sum of `octaves` layers of noise, each
+    /// one `lacunarity` times the frequency and `gain` times the amplitude
+    /// of the previous. Normalized by the total amplitude, so it stays in
+    /// about the same range as [noise](Self::noise).
+    pub fn fbm(&self, p: &Vec3, octaves: usize, lacunarity: f64, gain: f64) -> f64 {
+        let mut accum = 0.0;
+        let mut total = 0.0;
+        let mut temp_p = *p;
+        let mut weight = 1.0;
+
+        for _ in 0..octaves.max(1) {
+            accum += weight * self.noise(&temp_p);
+            total += weight;
+            weight *= gain;
+            temp_p *= lacunarity;
+        }
+
+        accum / total
+    }
 }
 
 impl PerlinNoiseGenerator {
@@ -85,7 +85,7 @@ fn box_compare(a: &dyn Hittable, b: &dyn Hittable, axis: usize) -> Ordering {
 }
 
 impl Hittable for BVH {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         use BVHNode::*;
         // If if does not hit the bounding box, return instantly
         if !self.b_box.hit(r, t_min, t_max) {
@@ -114,4 +114,51 @@ impl Hittable for BVH {
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
         Some(self.b_box.clone())
     }
+
+    /// Follows the same path as [hit](BVH::hit), counting this node and
+    /// the ones below it
+    fn node_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> u64 {
+        use BVHNode::*;
+        if !self.b_box.hit(r, t_min, t_max) {
+            return 1;
+        }
+
+        1 + match &self.node {
+            Leaf(leaf) => leaf.node_visits(r, t_min, t_max),
+            Node { left, right } => {
+                let t_right = left.hit(r, t_min, t_max).map_or(t_max, |rec| rec.t);
+                left.node_visits(r, t_min, t_max) + right.node_visits(r, t_min, t_right)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::object::Sphere;
+    use vec3::Vec3;
+
+    #[test]
+    fn node_visits() {
+        let objects: Vec<Box<dyn Hittable>> = (0..4)
+            .map(|i| {
+                Box::new(Sphere {
+                    center: Vec3::new(3.0 * i as f64, 0.0, 0.0),
+                    radius: 1.0,
+                    material: Lambertian::from_color(Vec3::one()),
+                }) as Box<dyn Hittable>
+            })
+            .collect();
+        let bvh = BVH::build(objects, 0.0, 1.0);
+
+        let miss = Ray::new(Vec3::new(0.0, 5.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        assert!(bvh.hit(&miss, 0.001, f64::INFINITY).is_none());
+        assert_eq!(bvh.node_visits(&miss, 0.001, f64::INFINITY), 1);
+
+        let r = Ray::new(Vec3::new(3.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(bvh.hit(&r, 0.001, f64::INFINITY).is_some());
+        assert!(bvh.node_visits(&r, 0.001, f64::INFINITY) > 1);
+    }
 }
@@ -31,13 +31,31 @@ pub struct Camera {
     vertical: Vec3,
     u: Vec3,
     v: Vec3,
-    #[allow(dead_code)]
     w: Vec3,
     lens_radius: f64,
+    /// Area of the image plane at distance 1 from the lens
+    image_plane_area: f64,
+    /// Height of the image plane at distance 1 from the lens
+    viewport_height: f64,
     time0: f64,
     time1: f64,
 }
 
+/// Result of sampling the camera lens towards a point of the scene with
+/// [Camera::sample_importance]
+pub struct ImportanceSample {
+    /// Sampled point of the lens
+    pub lens_point: Vec3,
+    /// Image coords where the point is seen
+    pub u: f64,
+    pub v: f64,
+    /// Importance emitted from the lens point towards the scene point
+    pub importance: f64,
+    /// Probability density (with respect to solid angle from the scene point)
+    /// of choosing the lens point
+    pub pdf: f64,
+}
+
 impl Camera {
     /// Creates a new [Camera] from a [CameraConfig]
     pub fn new(c: &CameraConfig, aspect_ratio: f64) -> Self {
@@ -57,6 +75,7 @@ impl Camera {
         let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - c.focus_distance * w;
 
         let lens_radius = c.aperture / 2.0;
+        let image_plane_area = viewport_width * viewport_height;
         Camera {
             origin,
             horizontal,
@@ -66,6 +85,8 @@ impl Camera {
             u,
             v,
             lens_radius,
+            image_plane_area,
+            viewport_height,
             time0: c.time0,
             time1: c.time1,
         }
@@ -82,4 +103,143 @@ impl Camera {
             rand::thread_rng().gen_range(self.time0..self.time1),
         )
     }
+
+    /// Angle covered by a pixel of an image `height` pixels tall, the
+    /// [spread](Ray::spread) of the rays that go through it
+    pub fn pixel_spread(&self, height: usize) -> f64 {
+        self.viewport_height / height as f64
+    }
+
+    /// Direction the camera is looking at
+    #[inline]
+    pub fn forward(&self) -> Vec3 {
+        -self.w
+    }
+
+    /// Area of the lens. A pinhole camera is considered to have area `1.0`.
+    #[inline]
+    fn lens_area(&self) -> f64 {
+        if self.lens_radius > 0.0 {
+            std::f64::consts::PI * self.lens_radius * self.lens_radius
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns the image coords (`u`, `v`) of a ray leaving the lens from
+    /// `lens_point` in `direction`, the inverse of [get_ray](Camera::get_ray).
+    /// Returns [None] if the ray is outside the image.
+    pub fn project(&self, lens_point: &Vec3, direction: &Vec3) -> Option<(f64, f64)> {
+        let cos_theta = direction.dot(&self.w);
+        if cos_theta >= 0.0 {
+            return None;
+        }
+        // Intersection with the focus plane
+        let t = (self.lower_left_corner - *lens_point).dot(&self.w) / cos_theta;
+        let q = *lens_point + t * *direction - self.lower_left_corner;
+        let u = q.dot(&self.horizontal) / self.horizontal.length_squared();
+        let v = q.dot(&self.vertical) / self.vertical.length_squared();
+        if (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v) {
+            Some((u, v))
+        } else {
+            None
+        }
+    }
+
+    /// Importance emitted by the camera from `lens_point` in `direction`.
+    /// It is normalized so its integral over the image is `1.0`.
+    pub fn importance(&self, lens_point: &Vec3, direction: &Vec3) -> f64 {
+        if self.project(lens_point, direction).is_none() {
+            return 0.0;
+        }
+        let cos_theta = direction.unit_vector().dot(&self.forward());
+        let cos2_theta = cos_theta * cos_theta;
+        1.0 / (self.image_plane_area * self.lens_area() * cos2_theta * cos2_theta)
+    }
+
+    /// Returns the probability densities of [get_ray](Camera::get_ray) choosing
+    /// a point of the lens (with respect to area) and choosing `direction`
+    /// (with respect to solid angle).
+    pub fn pdf_importance(&self, lens_point: &Vec3, direction: &Vec3) -> (f64, f64) {
+        if self.project(lens_point, direction).is_none() {
+            return (0.0, 0.0);
+        }
+        let cos_theta = direction.unit_vector().dot(&self.forward());
+        (
+            1.0 / self.lens_area(),
+            1.0 / (self.image_plane_area * cos_theta * cos_theta * cos_theta),
+        )
+    }
+
+    /// Samples a point of the lens that sees the given `point` of the scene.
+    /// Returns [None] if the point is not visible in the image.
+    pub fn sample_importance(&self, point: &Vec3) -> Option<ImportanceSample> {
+        let rd = self.lens_radius * Vec3::random_in_unit_disk();
+        let lens_point = self.origin + self.u * rd.x() + self.v * rd.y();
+
+        let direction = *point - lens_point;
+        let (u, v) = self.project(&lens_point, &direction)?;
+        let distance_squared = direction.length_squared();
+        let cos_lens = direction.unit_vector().dot(&self.forward()).abs();
+
+        Some(ImportanceSample {
+            lens_point,
+            u,
+            v,
+            importance: self.importance(&lens_point, &direction),
+            pdf: distance_squared / (cos_lens * self.lens_area()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pinhole() -> Camera {
+        let config = CameraConfig {
+            lookfrom: Vec3::zero(),
+            lookat: Vec3::new(0.0, 0.0, -1.0),
+            vup: Vec3::new(0.0, 1.0, 0.0),
+            vfov: 90.0,
+            aperture: 0.0,
+            focus_distance: 1.0,
+            time0: 0.0,
+            time1: 1.0,
+        };
+        Camera::new(&config, 1.0)
+    }
+
+    #[test]
+    fn importance_at_image_center() {
+        let camera = pinhole();
+        let r = camera.get_ray(0.5, 0.5);
+        let (u, v) = camera.project(&r.origin, &r.direction).unwrap();
+        assert!((u - 0.5).abs() < 1e-9 && (v - 0.5).abs() < 1e-9);
+
+        // The image plane at distance 1 is 2x2 and the direction is
+        // perpendicular to it
+        assert!((camera.importance(&r.origin, &r.direction) - 0.25).abs() < 1e-9);
+        let (pdf_pos, pdf_dir) = camera.pdf_importance(&r.origin, &r.direction);
+        assert_eq!(pdf_pos, 1.0);
+        assert!((pdf_dir - 0.25).abs() < 1e-9);
+
+        // Looking back there is nothing to see
+        assert_eq!(camera.importance(&r.origin, &-r.direction), 0.0);
+        assert_eq!(camera.pdf_importance(&r.origin, &-r.direction), (0.0, 0.0));
+    }
+
+    #[test]
+    fn importance_sampling() {
+        let camera = pinhole();
+        let sample = camera
+            .sample_importance(&Vec3::new(0.0, 0.0, -2.0))
+            .unwrap();
+        assert!((sample.u - 0.5).abs() < 1e-9 && (sample.v - 0.5).abs() < 1e-9);
+        assert!((sample.importance - 0.25).abs() < 1e-9);
+        assert!((sample.pdf - 4.0).abs() < 1e-9);
+        assert!(camera
+            .sample_importance(&Vec3::new(0.0, 0.0, 2.0))
+            .is_none());
+    }
 }
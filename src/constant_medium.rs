@@ -38,7 +38,7 @@ impl<H: Hittable> ConstantMedium<H, Isotropic<SolidColor>> {
 }
 
 impl<H: Hittable, M: Material> Hittable for ConstantMedium<H, M> {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         let mut hit1 = self.boundary.hit(r, f64::NEG_INFINITY, f64::INFINITY)?;
         let mut hit2 = self.boundary.hit(r, hit1.t + 0.0001, f64::INFINITY)?;
 
@@ -64,15 +64,10 @@ impl<H: Hittable, M: Material> Hittable for ConstantMedium<H, M> {
         let t = hit1.t + hit_distance / ray_length;
         let point = r.at(t);
         let normal = Vec3::new(1.0, 0.0, 0.0); // Some arbitrary value, and u, v
-        Some(HitRecord::new(
-            r,
-            t,
-            1.0,
-            1.0,
-            point,
-            normal,
-            &self.phase_function,
-        ))
+        Some(
+            HitRecord::new(r, t, 1.0, 1.0, point, normal, &self.phase_function)
+                .with_arbitrary_tangents(),
+        )
     }
 
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use vec3::Vec3;
+
+/// Buffer of light contributions ("splats") that can land in any pixel
+/// of the image, not only in the one being computed.
+///
+/// It's shared between all the render threads, so every subpixel is
+/// stored as the bits of an [f64] in an [AtomicU64].
+pub struct Film {
+    width: usize,
+    height: usize,
+    pixels: Vec<AtomicU64>,
+}
+
+impl Film {
+    /// Creates a new [Film] with every pixel set to black
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: (0..width * height * 3)
+                .map(|_| AtomicU64::new(0.0f64.to_bits()))
+                .collect(),
+        }
+    }
+
+    /// Adds `color` to the pixel containing the image coords (`u`, `v`),
+    /// the same as in [Camera::get_ray](crate::camera::Camera::get_ray).
+    pub fn add_splat(&self, u: f64, v: f64, color: Vec3) {
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return;
+        }
+        let i = (u * self.width as f64) as usize;
+        let j = (v * self.height as f64) as usize;
+        let index = 3 * (j * self.width + i);
+        for (c, value) in color.iter().enumerate() {
+            if *value == 0.0 || !value.is_finite() {
+                continue;
+            }
+            let _ =
+                self.pixels[index + c].fetch_update(Ordering::Relaxed, Ordering::Relaxed, |old| {
+                    Some((f64::from_bits(old) + value).to_bits())
+                });
+        }
+    }
+
+    /// Returns the color accumulated in the pixel (`i`, `j`), where `j` counts
+    /// from the bottom of the image.
+    pub fn get(&self, i: usize, j: usize) -> Vec3 {
+        let index = 3 * (j * self.width + i);
+        (0..3)
+            .map(|c| f64::from_bits(self.pixels[index + c].load(Ordering::Relaxed)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splats_accumulate_in_their_pixel() {
+        let film = Film::new(2, 2);
+        film.add_splat(0.75, 0.25, Vec3::new(1.0, 2.0, 3.0));
+        film.add_splat(0.9, 0.4, Vec3::new(1.0, 0.0, 0.0));
+        film.add_splat(1.0, 0.5, Vec3::one());
+        assert_eq!(film.get(1, 0), Vec3::new(2.0, 2.0, 3.0));
+        assert_eq!(film.get(0, 0), Vec3::zero());
+        assert_eq!(film.get(1, 1), Vec3::zero());
+    }
+}
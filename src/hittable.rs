@@ -1,21 +1,60 @@
 use crate::aabb::{surrounding_box, AABB};
 use crate::material::Material;
+use crate::onb::ONB;
 use crate::ray::Ray;
 use vec3::Vec3;
 
+use rand::Rng;
+
 /// Allows a struct to interact with light; being hitted by
 /// a ray
 pub trait Hittable: Send + Sync {
     /// Checks if the ray hits in the given time the struct and if so returns a
     /// [Some] value with a [HitRecord], else return [None]
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>>;
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB>;
+    #[allow(unused)]
+    /// Returns the probability density (with respect to solid angle) of
+    /// [random](Hittable::random) generating `direction` from `origin`. It's
+    /// used to sample lights explicitly. It has a default implementation
+    /// where the object cannot be sampled.
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        0.0
+    }
+    #[allow(unused)]
+    /// Returns a random direction from `origin` towards the object.
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+    #[allow(unused)]
+    /// Returns a random point of the surface of the object, as a [HitRecord]
+    /// whose normal points outwards, and the probability density (with
+    /// respect to area) of choosing it. It has a default implementation where
+    /// the surface cannot be sampled.
+    fn sample_surface(&self, time: f64) -> Option<(HitRecord<'_>, f64)> {
+        None
+    }
+    #[allow(unused)]
+    /// Returns the probability density (with respect to area) of
+    /// [sample_surface](Hittable::sample_surface) choosing the point where
+    /// a ray from `origin` in `direction` hits the object.
+    fn surface_pdf(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        0.0
+    }
+    #[allow(unused)]
+    /// Returns the number of [BVH](crate::bvh::BVH) nodes whose bounding box
+    /// is tested by [hit](Hittable::hit) with the same arguments. It's only
+    /// used to show the cost of a scene, so it's kept out of the hit. It
+    /// has a default implementation for objects without a BVH inside.
+    fn node_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> u64 {
+        0
+    }
 }
 
 /// Struct that implements [Hittable] but is never hittet neither it has a bounding box
 pub struct Unhittable;
 impl Hittable for Unhittable {
-    fn hit(&self, _r: &Ray, _t_min: f64, _t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, _r: &Ray, _t_min: f64, _t_max: f64) -> Option<HitRecord<'_>> {
         None
     }
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
@@ -24,20 +63,40 @@ impl Hittable for Unhittable {
 }
 
 /// Data returned from a ray hit into a [Hittable]
+#[derive(Clone)]
 pub struct HitRecord<'a> {
     pub point: Vec3,
+    /// Point of the hit in the space of the object, before the
+    /// [Translate](crate::object::Translate) and [RotateY](crate::object::RotateY)
+    /// that place it in the world
+    pub object_point: Vec3,
+    /// Normal used for shading. Usually the same as the
+    /// [geometric_normal](HitRecord::geometric_normal), but it can be
+    /// perturbed by normal or bump maps.
     pub normal: Vec3,
+    /// Normal of the actual surface. Points against the ray.
+    pub geometric_normal: Vec3,
+    /// Tangent of the surface: derivative of the point with respect to `u`
+    pub dpdu: Vec3,
+    /// Bitangent of the surface: derivative of the point with respect to `v`
+    pub dpdv: Vec3,
     pub t: f64,
     pub u: f64,
     pub v: f64,
     pub front_face: bool,
     pub material: &'a dyn Material,
+    /// Width of the footprint of the ray at the hit (see [Ray::spread])
+    pub footprint: f64,
 }
 
 impl<'a> HitRecord<'a> {
     /// Creates a new [HitRecord] where the normal will
     /// always points against the ray and [front_face](HitRecord::front_face)
     /// tell us if the normal is points inwards or outwards the object.
+    ///
+    /// The tangents are left at zero. Objects that know how `u` and `v`
+    /// change set them with [with_tangents](HitRecord::with_tangents), and
+    /// the rest with [with_arbitrary_tangents](HitRecord::with_arbitrary_tangents).
     pub fn new(
         r: &Ray,
         t: f64,
@@ -49,20 +108,56 @@ impl<'a> HitRecord<'a> {
     ) -> Self {
         let mut hr = Self {
             point,
+            object_point: point,
             normal: Vec3::zero(),
+            geometric_normal: Vec3::zero(),
+            dpdu: Vec3::zero(),
+            dpdv: Vec3::zero(),
             t,
             u,
             v,
             front_face: false,
             material,
+            footprint: r.spread * t * r.direction.length(),
         };
 
         hr.set_face_normal(r, outward_normal);
         hr
     }
 
-    /// Sets the [front_face](HitRecord::front_face) and [normal][HitRecord::normal]
-    /// calculating it from the given ray and outward normal (outward from the object)
+    /// Sets the derivatives of the point with respect to `u` and `v`
+    pub fn with_tangents(mut self, dpdu: Vec3, dpdv: Vec3) -> Self {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        self
+    }
+
+    /// Sets any two unit tangents perpendicular to the normal, for objects
+    /// without a parametrization of their surface
+    pub fn with_arbitrary_tangents(self) -> Self {
+        let outward_normal = if self.front_face {
+            self.normal
+        } else {
+            -self.normal
+        };
+        let onb = ONB::build_from_w(&outward_normal);
+        self.with_tangents(onb.u, onb.v)
+    }
+
+    /// Width of the [footprint](HitRecord::footprint) in surface
+    /// coordinates, along the direction in which they change the slowest
+    pub fn uv_footprint(&self) -> f64 {
+        let scale = self.dpdu.length().min(self.dpdv.length());
+        if scale > 0.0 {
+            self.footprint / scale
+        } else {
+            0.0
+        }
+    }
+
+    /// Sets the [front_face](HitRecord::front_face), [normal][HitRecord::normal]
+    /// and [geometric_normal][HitRecord::geometric_normal] calculating them
+    /// from the given ray and outward normal (outward from the object)
     pub fn set_face_normal(&mut self, r: &Ray, outward_normal: Vec3) {
         self.front_face = r.direction.dot(&outward_normal) < 0.0;
         self.normal = if self.front_face {
@@ -70,6 +165,34 @@ impl<'a> HitRecord<'a> {
         } else {
             -outward_normal
         };
+        self.geometric_normal = self.normal;
+    }
+
+    /// Returns the same hit as if the ray `r` had found it, with the
+    /// normals pointing against `r`
+    pub fn seen_from(&self, r: &Ray) -> Self {
+        let mut hit = self.clone();
+        if r.direction.dot(&self.geometric_normal) > 0.0 {
+            hit.normal = -hit.normal;
+            hit.geometric_normal = -hit.geometric_normal;
+            hit.front_face = !hit.front_face;
+        }
+        hit
+    }
+
+    /// Returns the same hit shaded with `normal` instead of its
+    /// [normal](HitRecord::normal). It is turned to the side of the
+    /// geometric normal and the tangents are made perpendicular to it.
+    pub fn with_shading_normal(&self, normal: Vec3) -> Self {
+        let mut normal = normal.unit_vector();
+        if normal.dot(&self.geometric_normal) < 0.0 {
+            normal = -normal;
+        }
+        let mut hit = self.clone();
+        hit.normal = normal;
+        hit.dpdu = self.dpdu - normal.dot(&self.dpdu) * normal;
+        hit.dpdv = self.dpdv - normal.dot(&self.dpdv) * normal;
+        hit
     }
 }
 
@@ -81,7 +204,7 @@ pub struct HittableList {
 impl Hittable for HittableList {
     /// Returns the hit of the closer object. Tests the hit for every object in the
     /// list.
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         let mut rec: Option<HitRecord> = None;
         let mut closest_so_far = t_max;
 
@@ -95,6 +218,22 @@ impl Hittable for HittableList {
         rec
     }
 
+    /// Returns the visits of every object, shortening the ray as
+    /// [hit](HittableList::hit) does
+    fn node_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> u64 {
+        let mut visits = 0;
+        let mut closest_so_far = t_max;
+
+        for object in &self.objects {
+            visits += object.node_visits(r, t_min, closest_so_far);
+            if let Some(hit) = object.hit(r, t_min, closest_so_far) {
+                closest_so_far = hit.t;
+            }
+        }
+
+        visits
+    }
+
     /// Returns the bounding box of all the elements of the list
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
         if self.objects.is_empty() {
@@ -111,4 +250,50 @@ impl Hittable for HittableList {
             Some(temp_box)
         }
     }
+
+    /// Returns the average of the pdf of all the elements of the list
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+        let weight = 1.0 / self.objects.len() as f64;
+        self.objects
+            .iter()
+            .map(|object| weight * object.pdf_value(origin, direction))
+            .sum()
+    }
+
+    /// Returns a random direction towards a random element of the list
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+        let i = rand::thread_rng().gen_range(0..self.objects.len());
+        self.objects[i].random(origin)
+    }
+
+    /// Samples the surface of a random element of the list
+    fn sample_surface(&self, time: f64) -> Option<(HitRecord<'_>, f64)> {
+        if self.objects.is_empty() {
+            return None;
+        }
+        let i = rand::thread_rng().gen_range(0..self.objects.len());
+        let (hit, pdf) = self.objects[i].sample_surface(time)?;
+        Some((hit, pdf / self.objects.len() as f64))
+    }
+
+    /// Returns the surface pdf of the closest element of the list hit
+    fn surface_pdf(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        let r = Ray::new(*origin, *direction, 0.0);
+        let mut closest: Option<(f64, &Box<dyn Hittable>)> = None;
+        for object in &self.objects {
+            let t_max = closest.map_or(f64::INFINITY, |(t, _)| t);
+            if let Some(hit) = object.hit(&r, 0.001, t_max) {
+                closest = Some((hit.t, object));
+            }
+        }
+        closest.map_or(0.0, |(_, object)| {
+            object.surface_pdf(origin, direction) / self.objects.len() as f64
+        })
+    }
 }
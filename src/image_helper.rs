@@ -11,14 +11,23 @@ fn f64_subpixel_to_u8(p: f64) -> u8 {
     p.sqrt().clamp_(0.0, 0.999).mul(256.0).floor() as u8
 }
 
-pub trait Image {
+pub trait Image: Sized {
     fn par_compute(
         width: usize,
         height: usize,
         threads: usize,
         tx: mpsc::Sender<bool>,
         f: impl Fn(usize, usize) -> Vec3 + Sync + Send + 'static,
-    ) -> Self;
+    ) -> Self {
+        Self::from_pixels(
+            width,
+            height,
+            &par_compute_pixels(width, height, threads, tx, f),
+        )
+    }
+    /// Builds the image from the linear colors of its pixels, row by row
+    /// starting from the top.
+    fn from_pixels(width: usize, height: usize, pixels: &[Vec3]) -> Self;
     fn write_as_plain_ppm(&self, file: &mut impl Write) -> Result<(), Error>;
 }
 
@@ -27,7 +36,7 @@ fn compute_sanlines(
     width: usize,
     progress_tx: mpsc::Sender<bool>,
     f: Arc<impl Fn(usize, usize) -> Vec3>,
-) -> Vec<u8> {
+) -> Vec<Vec3> {
     scanlines
         .rev()
         .flat_map(|j| {
@@ -39,61 +48,71 @@ fn compute_sanlines(
                 })
                 .collect::<Vec<Vec3>>()
         })
-        .flat_map(|v| Vec::from(v.v))
-        .map(f64_subpixel_to_u8)
         .collect()
 }
 
-impl Image for image::RgbImage {
-    fn par_compute(
-        width: usize,
-        height: usize,
-        threads: usize,
-        tx: mpsc::Sender<bool>,
-        f: impl Fn(usize, usize) -> Vec3 + Sync + Send + 'static,
-    ) -> Self {
-        // Calculations on how to distribute the scanlines evenly
-        let low = height / threads;
-        let high = low + if height % threads == 0 { 0 } else { 1 };
-
-        let n_high = if high == low {
-            threads
-        } else {
-            threads - (threads * high - height) / (high - low)
-        };
-
-        let mut pos = 0;
-
-        let mut handles = Vec::new();
-
-        let f = Arc::new(f);
-
-        for t in 0..threads {
-            let w = if t < n_high { high } else { low };
-            let start = pos;
-            let end = pos + w;
-            pos += w;
-
-            let tx = tx.clone();
-            let f = Arc::clone(&f);
+/// Computes in parallel the color of every pixel with `f(i, j)`, where `j`
+/// counts from the bottom of the image. The pixels are returned row by row
+/// starting from the top.
+pub fn par_compute_pixels(
+    width: usize,
+    height: usize,
+    threads: usize,
+    tx: mpsc::Sender<bool>,
+    f: impl Fn(usize, usize) -> Vec3 + Sync + Send + 'static,
+) -> Vec<Vec3> {
+    // Calculations on how to distribute the scanlines evenly
+    let low = height / threads;
+    let high = low + if height.is_multiple_of(threads) { 0 } else { 1 };
+
+    let n_high = if high == low {
+        threads
+    } else {
+        threads - (threads * high - height) / (high - low)
+    };
+
+    let mut pos = 0;
+
+    let mut handles = Vec::new();
+
+    let f = Arc::new(f);
+
+    for t in 0..threads {
+        let w = if t < n_high { high } else { low };
+        let start = pos;
+        let end = pos + w;
+        pos += w;
+
+        let tx = tx.clone();
+        let f = Arc::clone(&f);
+
+        let handle = std::thread::spawn(move || compute_sanlines(start..end, width, tx, f));
+        handles.push(handle);
+    }
 
-            let handle = std::thread::spawn(move || compute_sanlines(start..end, width, tx, f));
-            handles.push(handle);
-        }
+    handles
+        .into_iter()
+        .rev()
+        .flat_map(|h| h.join().unwrap())
+        .collect()
+}
 
-        let pixels = handles
-            .into_iter()
-            .rev()
-            .flat_map(|h| h.join().unwrap())
+impl Image for image::RgbImage {
+    fn from_pixels(width: usize, height: usize, pixels: &[Vec3]) -> Self {
+        let subpixels = pixels
+            .iter()
+            .flat_map(|p| p.iter())
+            .copied()
+            .map(f64_subpixel_to_u8)
             .collect::<Vec<u8>>();
 
-        image::RgbImage::from_vec(width as u32, height as u32, pixels)
+        image::RgbImage::from_vec(width as u32, height as u32, subpixels)
             .expect("Image could not be built from pixels")
     }
 
     fn write_as_plain_ppm(&self, file: &mut impl Write) -> Result<(), Error> {
         let header = format!("P3\n{} {}\n255\n", self.width(), self.height());
-        file.write_all(&header.as_bytes())?;
+        file.write_all(header.as_bytes())?;
 
         let buffer: String = self
             .pixels()
@@ -103,7 +122,7 @@ impl Image for image::RgbImage {
             })
             .collect();
 
-        file.write_all(&buffer.as_bytes())?;
+        file.write_all(buffer.as_bytes())?;
         Ok(())
     }
 }
@@ -0,0 +1,48 @@
+use super::*;
+
+/// Ambient occlusion
+///
+/// Ignores materials and lights. Returns white when a random direction
+/// over the hemisphere of the first hit is not blocked by any object
+/// closer than [max_distance](AmbientOcclusion::max_distance), and black
+/// if it is.
+#[derive(Clone)]
+pub struct AmbientOcclusion {
+    /// Distance from which objects do not occlude. Infinity means any object
+    /// occludes.
+    pub max_distance: f64,
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        Self {
+            max_distance: f64::INFINITY,
+        }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3 {
+        let hit = match scene.hit(r) {
+            Some(hit) => hit,
+            None => return Vec3::one(),
+        };
+
+        let mut direction = hit.normal + Vec3::random_unit_vector();
+        // Catch direction near 0
+        if direction.near_zero() {
+            direction = hit.normal;
+        }
+
+        let occlusion_ray = Ray::new(hit.point, direction.unit_vector(), r.time);
+        if scene
+            .world
+            .hit(&occlusion_ray, HIT_T_MIN, self.max_distance)
+            .is_some()
+        {
+            Vec3::zero()
+        } else {
+            Vec3::one()
+        }
+    }
+}
@@ -0,0 +1,609 @@
+use super::*;
+use crate::onb::ONB;
+use std::f64::consts::TAU;
+
+/// Bidirectional path tracer
+///
+/// Traces a subpath from the camera and another one from a random point
+/// of the scene lights, and connects every vertex of one with every vertex
+/// of the other. All these strategies are combined with multiple
+/// importance sampling. Connections to the camera land in any pixel, so
+/// they are splatted into the [Film].
+///
+/// Much better than the path tracers with caustics or light coming through
+/// small openings, but slower per sample. Delta lights, like a
+/// [PointLight](crate::light::PointLight), and the environment are only
+/// connected to the camera subpath, so their caustics are not found.
+#[derive(Clone)]
+pub struct BidirectionalPathTracer;
+
+/// Number of bounces before Russian roulette can terminate a subpath
+const RUSSIAN_ROULETTE_DEPTH: usize = 3;
+
+#[derive(Clone)]
+enum VertexKind<'a> {
+    /// Point of the camera lens
+    Camera,
+    /// Point of the surface of a light, the start of a light subpath
+    Light(HitRecord<'a>),
+    /// Point where light scatters, with the ray that arrived to it
+    Surface(HitRecord<'a>, Ray),
+}
+
+/// Vertex of a camera or light subpath
+#[derive(Clone)]
+struct Vertex<'a> {
+    kind: VertexKind<'a>,
+    point: Vec3,
+    /// Geometric normal of the surface. Zero for the camera and points inside a medium.
+    normal: Vec3,
+    /// Throughput of the subpath up to this vertex
+    beta: Vec3,
+    /// The material scatters with a delta distribution, so it cannot be connected
+    delta: bool,
+    /// Density (with respect to area) of sampling this vertex from the previous one
+    pdf_fwd: f64,
+    /// Density (with respect to area) of sampling this vertex from the next one
+    pdf_rev: f64,
+}
+
+impl<'a> Vertex<'a> {
+    fn camera(point: Vec3, beta: Vec3) -> Self {
+        Self {
+            kind: VertexKind::Camera,
+            point,
+            normal: Vec3::zero(),
+            beta,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn light(hit: HitRecord<'a>, beta: Vec3, pdf_fwd: f64) -> Self {
+        Self {
+            point: hit.point,
+            normal: hit.geometric_normal,
+            kind: VertexKind::Light(hit),
+            beta,
+            delta: false,
+            pdf_fwd,
+            pdf_rev: 0.0,
+        }
+    }
+
+    #[inline]
+    fn on_surface(&self) -> bool {
+        !self.normal.near_zero()
+    }
+
+    fn is_connectible(&self) -> bool {
+        match self.kind {
+            VertexKind::Surface(..) => !self.delta,
+            _ => true,
+        }
+    }
+
+    /// Light emitted by the vertex
+    fn le(&self) -> Vec3 {
+        match &self.kind {
+            VertexKind::Light(hit) | VertexKind::Surface(hit, _) => hit.material.emitted(hit),
+            VertexKind::Camera => Vec3::zero(),
+        }
+    }
+
+    /// Scattering function (including the cosine) towards `next`
+    fn f(&self, next: &Vertex) -> Vec3 {
+        match &self.kind {
+            VertexKind::Surface(hit, r_in) => {
+                hit.material.eval(r_in, hit, &(next.point - self.point))
+            }
+            _ => Vec3::zero(),
+        }
+    }
+
+    /// Converts a density with respect to solid angle from this vertex to
+    /// a density with respect to area at `next`
+    fn convert_density(&self, pdf: f64, next: &Vertex) -> f64 {
+        let w = next.point - self.point;
+        let distance_squared = w.length_squared();
+        if distance_squared == 0.0 {
+            return 0.0;
+        }
+        let cosine = if next.on_surface() {
+            next.normal.dot(&w.unit_vector()).abs()
+        } else {
+            1.0
+        };
+        pdf * cosine / distance_squared
+    }
+
+    /// Density (with respect to area) of sampling `next` from this vertex
+    /// when the path arrived from `prev`
+    fn pdf(&self, scene: &SceneContext, prev: Option<&Vertex>, next: &Vertex) -> f64 {
+        match &self.kind {
+            VertexKind::Camera => {
+                let (_, pdf_dir) = scene
+                    .camera
+                    .pdf_importance(&self.point, &(next.point - self.point));
+                self.convert_density(pdf_dir, next)
+            }
+            VertexKind::Light(_) => self.pdf_light(next),
+            VertexKind::Surface(hit, r_in) => {
+                let prev = match prev {
+                    Some(prev) => prev,
+                    None => return 0.0,
+                };
+                let r_in = Ray::new(prev.point, self.point - prev.point, r_in.time);
+                let pdf = hit.material.scattering_pdf(
+                    &r_in,
+                    &hit.seen_from(&r_in),
+                    &(next.point - self.point),
+                );
+                self.convert_density(pdf, next)
+            }
+        }
+    }
+
+    /// Density (with respect to area) of the light at this vertex emitting
+    /// towards `next`
+    fn pdf_light(&self, next: &Vertex) -> f64 {
+        let w = next.point - self.point;
+        let pdf_dir = self.normal.dot(&w.unit_vector()).abs() / TAU;
+        self.convert_density(pdf_dir, next)
+    }
+
+    /// Density (with respect to area) of the scene lights choosing this vertex
+    fn pdf_light_origin(&self, scene: &SceneContext, prev: &Vertex) -> f64 {
+        scene
+            .lights
+            .surface_pdf(&prev.point, &(self.point - prev.point))
+    }
+}
+
+/// Extends `path` bouncing `ray` through the scene until it has `max_vertices`.
+/// If the path escaped the scene, returns its throughput, the last ray and
+/// the density (with respect to solid angle) it was sampled with.
+fn random_walk<'a>(
+    scene: &SceneContext<'a>,
+    mut ray: Ray,
+    mut beta: Vec3,
+    pdf: f64,
+    max_vertices: usize,
+    path: &mut Vec<Vertex<'a>>,
+) -> Option<(Vec3, Ray, f64)> {
+    let mut pdf_fwd = pdf;
+    let mut bounces = 0;
+
+    while path.len() < max_vertices {
+        let hit = match scene.hit(&ray) {
+            Some(hit) => hit,
+            None => return Some((beta, ray, pdf_fwd)),
+        };
+
+        let mut vertex = Vertex {
+            point: hit.point,
+            normal: if hit.material.is_volumetric() {
+                Vec3::zero()
+            } else {
+                hit.geometric_normal
+            },
+            kind: VertexKind::Surface(hit.clone(), ray.clone()),
+            beta,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        };
+        vertex.pdf_fwd = path.last().unwrap().convert_density(pdf_fwd, &vertex);
+        path.push(vertex);
+
+        if path.len() >= max_vertices {
+            break;
+        }
+
+        let (attenuation, scattered) = match hit.material.scatter(&ray, &hit) {
+            Some(s) => s,
+            None => break,
+        };
+
+        let n = path.len();
+        let pdf_rev = if hit.material.is_specular(&hit) {
+            path[n - 1].delta = true;
+            pdf_fwd = 0.0;
+            0.0
+        } else {
+            pdf_fwd = hit
+                .material
+                .scattering_pdf(&ray, &hit, &scattered.direction);
+            let reversed = Ray::new(
+                hit.point + scattered.direction,
+                -scattered.direction,
+                ray.time,
+            );
+            hit.material
+                .scattering_pdf(&reversed, &hit.seen_from(&reversed), &-ray.direction)
+        };
+        path[n - 2].pdf_rev = path[n - 1].convert_density(pdf_rev, &path[n - 2]);
+
+        beta *= attenuation;
+
+        bounces += 1;
+        if bounces > RUSSIAN_ROULETTE_DEPTH {
+            let survival = attenuation.reduce(f64::max).min(0.95);
+            if rand::random::<f64>() >= survival {
+                break;
+            }
+            beta /= survival;
+        }
+
+        ray = scattered;
+    }
+
+    None
+}
+
+/// Traces a subpath starting at a random point of the scene lights
+fn light_subpath<'a>(scene: &SceneContext<'a>, time: f64, max_vertices: usize) -> Vec<Vertex<'a>> {
+    let mut path = Vec::with_capacity(max_vertices);
+    let (light_hit, pdf_pos) = match scene.lights.sample_surface(time) {
+        Some(sample) => sample,
+        None => return path,
+    };
+
+    // Lights may emit from both faces, so pick one of them
+    let side = if rand::random() {
+        light_hit.normal
+    } else {
+        -light_hit.normal
+    };
+    let local = Vec3::random_cosine_direction();
+    let direction = ONB::build_from_w(&side).local_vec(&local);
+    let pdf_dir = local.z() / TAU;
+    let le = light_hit.material.emitted(&light_hit.seen_from(&Ray::new(
+        light_hit.point,
+        -direction,
+        time,
+    )));
+
+    let ray = Ray::new(light_hit.point, direction, time);
+    path.push(Vertex::light(light_hit, le / pdf_pos, pdf_pos));
+    let beta = le * (local.z() / (pdf_pos * pdf_dir));
+    random_walk(scene, ray, beta, pdf_dir, max_vertices, &mut path);
+    path
+}
+
+/// Returns `true` if nothing blocks the segment from `a` to `b`
+fn visible(scene: &SceneContext, a: &Vec3, b: &Vec3, time: f64) -> bool {
+    let direction = *b - *a;
+    let distance = direction.length();
+    let r = Ray::new(*a, direction / distance, time);
+    scene
+        .world
+        .hit(&r, HIT_T_MIN, distance - HIT_T_MIN)
+        .is_none()
+}
+
+/// Multiple importance sampling weight (balance heuristic) of the path
+/// built with `s` light vertices and `t` camera vertices. `sampled` replaces
+/// the last vertex of the subpath with only 1 vertex.
+fn mis_weight(
+    scene: &SceneContext,
+    light: &[Vertex],
+    camera: &[Vertex],
+    sampled: Option<&Vertex>,
+    s: usize,
+    t: usize,
+) -> f64 {
+    if s + t == 2 {
+        return 1.0;
+    }
+    let remap0 = |f: f64| if f != 0.0 { f } else { 1.0 };
+
+    // Vertices around the connection
+    let qs = match s {
+        0 => None,
+        1 => sampled,
+        _ => Some(&light[s - 1]),
+    };
+    let pt = if t == 1 {
+        sampled.unwrap()
+    } else {
+        &camera[t - 1]
+    };
+    let qs_minus = if s > 1 { Some(&light[s - 2]) } else { None };
+    let pt_minus = if t > 1 { Some(&camera[t - 2]) } else { None };
+
+    // Their reverse densities for this strategy
+    let pt_pdf_rev = match qs {
+        Some(qs) => qs.pdf(scene, qs_minus, pt),
+        None => pt.pdf_light_origin(scene, pt_minus.unwrap()),
+    };
+    if s == 0 && pt_pdf_rev == 0.0 {
+        // The emitter is not a scene light, only found by chance
+        return 1.0;
+    }
+    let pt_minus_pdf_rev = pt_minus.map_or(0.0, |pt_minus| match qs {
+        Some(qs) => pt.pdf(scene, Some(qs), pt_minus),
+        None => pt.pdf_light(pt_minus),
+    });
+    let qs_pdf_rev = qs.map_or(0.0, |qs| pt.pdf(scene, pt_minus, qs));
+    let qs_minus_pdf_rev =
+        qs_minus.map_or(0.0, |qs_minus| qs.unwrap().pdf(scene, Some(pt), qs_minus));
+
+    let mut sum_ri = 0.0;
+
+    let mut ri = 1.0;
+    for i in (1..t).rev() {
+        let (pdf_rev, delta) = if i == t - 1 {
+            (pt_pdf_rev, false)
+        } else if i == t - 2 {
+            (pt_minus_pdf_rev, camera[i].delta)
+        } else {
+            (camera[i].pdf_rev, camera[i].delta)
+        };
+        ri *= remap0(pdf_rev) / remap0(camera[i].pdf_fwd);
+        if !delta && !camera[i - 1].delta {
+            sum_ri += ri;
+        }
+    }
+
+    let mut ri = 1.0;
+    for i in (0..s).rev() {
+        let (pdf_rev, pdf_fwd, delta) = if i == s - 1 {
+            (qs_pdf_rev, qs.unwrap().pdf_fwd, false)
+        } else if i == s - 2 {
+            (qs_minus_pdf_rev, light[i].pdf_fwd, light[i].delta)
+        } else {
+            (light[i].pdf_rev, light[i].pdf_fwd, light[i].delta)
+        };
+        ri *= remap0(pdf_rev) / remap0(pdf_fwd);
+        let delta_prev = i > 0 && light[i - 1].delta;
+        if !delta && !delta_prev {
+            sum_ri += ri;
+        }
+    }
+
+    1.0 / (1.0 + sum_ri)
+}
+
+/// Connects the first `s` vertices of the light subpath with the first `t`
+/// of the camera subpath. Returns the weighted light of the path and, if it
+/// has to be splatted, the image coords where it lands.
+fn connect(
+    scene: &SceneContext,
+    light: &[Vertex],
+    camera: &[Vertex],
+    s: usize,
+    t: usize,
+    time: f64,
+) -> (Vec3, Option<(f64, f64)>) {
+    let nothing = (Vec3::zero(), None);
+    let mut sampled = None;
+    let mut raster = None;
+
+    let radiance = if s == 0 {
+        // The camera subpath hits a light by itself
+        let pt = &camera[t - 1];
+        pt.le() * pt.beta
+    } else if t == 1 {
+        // Connect the light subpath to the camera
+        let qs = &light[s - 1];
+        if !qs.is_connectible() {
+            return nothing;
+        }
+        let sample = match scene.camera.sample_importance(&qs.point) {
+            Some(sample) => sample,
+            None => return nothing,
+        };
+        let camera_vertex = Vertex::camera(
+            sample.lens_point,
+            Vec3::splat(sample.importance / sample.pdf),
+        );
+        let radiance = qs.beta * qs.f(&camera_vertex) * camera_vertex.beta;
+        if radiance.near_zero() || !visible(scene, &qs.point, &camera_vertex.point, time) {
+            return nothing;
+        }
+        raster = Some((sample.u, sample.v));
+        sampled = Some(camera_vertex);
+        radiance
+    } else if s == 1 {
+        // Connect the camera subpath to a new point of the lights
+        let pt = &camera[t - 1];
+        if !pt.is_connectible() {
+            return nothing;
+        }
+        let (light_hit, pdf_pos) = match scene.lights.sample_surface(time) {
+            Some(sample) => sample,
+            None => return nothing,
+        };
+        let le = light_hit.material.emitted(&light_hit.seen_from(&Ray::new(
+            pt.point,
+            light_hit.point - pt.point,
+            time,
+        )));
+        let light_vertex = Vertex::light(light_hit, le / pdf_pos, pdf_pos);
+
+        let w = pt.point - light_vertex.point;
+        let cosine = light_vertex.normal.dot(&w.unit_vector()).abs();
+        let radiance =
+            pt.beta * pt.f(&light_vertex) * light_vertex.beta * (cosine / w.length_squared());
+        if radiance.near_zero() || !visible(scene, &pt.point, &light_vertex.point, time) {
+            return nothing;
+        }
+        sampled = Some(light_vertex);
+        radiance
+    } else {
+        // Connect both subpaths
+        let qs = &light[s - 1];
+        let pt = &camera[t - 1];
+        if !qs.is_connectible() || !pt.is_connectible() {
+            return nothing;
+        }
+        let distance_squared = (pt.point - qs.point).length_squared();
+        let radiance = qs.beta * qs.f(pt) * pt.f(qs) * pt.beta / distance_squared;
+        if radiance.near_zero() || !visible(scene, &pt.point, &qs.point, time) {
+            return nothing;
+        }
+        radiance
+    };
+
+    if radiance.near_zero() {
+        return nothing;
+    }
+
+    let weight = mis_weight(scene, light, camera, sampled.as_ref(), s, t);
+    (radiance * weight, raster)
+}
+
+impl Integrator for BidirectionalPathTracer {
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3 {
+        let max_bounces = scene.max_bounces as usize;
+        let mut radiance = Vec3::zero();
+
+        let mut camera_path = Vec::with_capacity(max_bounces + 2);
+        camera_path.push(Vertex::camera(r.origin, Vec3::one()));
+        let (_, pdf_dir) = scene.camera.pdf_importance(&r.origin, &r.direction);
+        if let Some((beta, escaped, pdf)) = random_walk(
+            scene,
+            r.clone(),
+            Vec3::one(),
+            pdf_dir,
+            max_bounces + 2,
+            &mut camera_path,
+        ) {
+            let last = camera_path.last().unwrap();
+            let weight = if camera_path.len() == 1 || last.delta {
+                1.0
+            } else {
+                environment_weight(scene, pdf, &escaped.direction)
+            };
+            radiance += beta * scene.environment.le(&escaped) * weight;
+        }
+
+        // Light subpaths cannot start at delta lights or the environment,
+        // so the camera subpath is only connected to them, like a path
+        // tracer would
+        for pt in camera_path.iter().skip(1).take(max_bounces) {
+            if let VertexKind::Surface(hit, r_in) = &pt.kind {
+                if !pt.delta {
+                    radiance += pt.beta
+                        * (sample_delta_lights(r_in, hit, scene)
+                            + sample_environment(r_in, hit, scene));
+                }
+            }
+        }
+
+        let light_path = light_subpath(scene, r.time, max_bounces + 1);
+
+        for t in 1..=camera_path.len() {
+            for s in 0..=light_path.len() {
+                let depth = s + t;
+                if (s == 1 && t == 1) || depth < 2 || depth - 2 > max_bounces {
+                    continue;
+                }
+                let (contribution, raster) =
+                    connect(scene, &light_path, &camera_path, s, t, r.time);
+                match raster {
+                    Some((u, v)) => scene.film.add_splat(u, v, contribution),
+                    None => radiance += contribution,
+                }
+            }
+        }
+
+        radiance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::CameraConfig;
+    use crate::hittable::HittableList;
+    use crate::material::{DiffuseLight, Lambertian};
+    use crate::object::{Rect, XZ};
+
+    /// Average color of a small image of a diffuse floor lit by a square
+    /// light, with the splats of the film included
+    fn render_mean(integrator: &dyn Integrator) -> Vec3 {
+        let (width, height, spp) = (8, 8, 128);
+        let light = Rect {
+            in_plane: XZ,
+            a0: -0.5,
+            a1: 0.5,
+            b0: -0.5,
+            b1: 0.5,
+            k: 2.0,
+            material: DiffuseLight::from_color(Vec3::splat(4.0)),
+        };
+        let world = HittableList {
+            objects: vec![
+                Box::new(light.clone()),
+                Box::new(Rect {
+                    in_plane: XZ,
+                    a0: -2.0,
+                    a1: 2.0,
+                    b0: -2.0,
+                    b1: 2.0,
+                    k: 0.0,
+                    material: Lambertian::from_color(Vec3::splat(0.5)),
+                }),
+            ],
+        };
+        let lights = HittableList {
+            objects: vec![Box::new(light)],
+        };
+        let camera = Camera::new(
+            &CameraConfig {
+                lookfrom: Vec3::new(0.0, 1.0, 3.0),
+                lookat: Vec3::zero(),
+                vup: Vec3::new(0.0, 1.0, 0.0),
+                vfov: 50.0,
+                aperture: 0.0,
+                focus_distance: 1.0,
+                time0: 0.0,
+                time1: 1.0,
+            },
+            1.0,
+        );
+        let film = Film::new(width, height);
+        let scene = SceneContext {
+            world: &world,
+            lights: &lights,
+            delta_lights: &[],
+            camera: &camera,
+            film: &film,
+            environment: &EnvironmentLight::from_color(Vec3::zero()),
+            max_bounces: 4,
+        };
+
+        let mut sum = Vec3::zero();
+        for j in 0..height {
+            for i in 0..width {
+                for _ in 0..spp {
+                    let u = (i as f64 + rand::random::<f64>()) / width as f64;
+                    let v = (j as f64 + rand::random::<f64>()) / height as f64;
+                    sum += integrator.radiance(&camera.get_ray(u, v), &scene);
+                }
+            }
+        }
+        for j in 0..height {
+            for i in 0..width {
+                sum += film.get(i, j);
+            }
+        }
+        sum / (width * height * spp) as f64
+    }
+
+    #[test]
+    fn agrees_with_explicit_path_tracer() {
+        let bidirectional = render_mean(&BidirectionalPathTracer);
+        let explicit = render_mean(&ExplicitPathTracer);
+        assert!(explicit.x() > 0.0);
+        assert!(
+            (bidirectional - explicit).length() < 0.05 * explicit.length(),
+            "bidirectional {:?} explicit {:?}",
+            bidirectional,
+            explicit
+        );
+    }
+}
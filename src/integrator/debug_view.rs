@@ -0,0 +1,244 @@
+use super::*;
+use crate::material::Material;
+use crate::Clampable;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Debug visualizations
+///
+/// These modes bypass light transport and show the data of the first
+/// [HitRecord] as colors, useful to diagnose geometry and texture
+/// coordinates problems. Rays that hit nothing are black.
+#[derive(Clone)]
+pub enum DebugView {
+    /// Outward surface normal, mapping each coord from [`-1.0`, `1.0`] to a color channel
+    Normals,
+    /// Texture coordinates `u` and `v` as the red and green channels
+    UV,
+    /// Heatmap of the hit distance, from the closest to the farthest point of
+    /// the world bounding box
+    Distance,
+    /// Green if the hit is in the front face, red if it's in the back face
+    FrontFace,
+    /// Heatmap of the number of [BVH](crate::bvh::BVH) nodes visited
+    BVHCost {
+        /// Number of visited nodes mapped to the hottest color
+        max_cost: u64,
+    },
+    /// Random color for each material instance
+    MaterialId,
+}
+
+impl DebugView {
+    /// Builds a [DebugView] from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "normals" => Some(Self::Normals),
+            "uv" => Some(Self::UV),
+            "distance" => Some(Self::Distance),
+            "front_face" => Some(Self::FrontFace),
+            "bvh_cost" => Some(Self::BVHCost { max_cost: 100 }),
+            "material_id" => Some(Self::MaterialId),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the names of the available debug views
+pub fn get_debug_views() -> [&'static str; 6] {
+    [
+        "normals",
+        "uv",
+        "distance",
+        "front_face",
+        "bvh_cost",
+        "material_id",
+    ]
+}
+
+/// Maps a value in [`0.0`, `1.0`] to a blue-green-red color
+fn heatmap(x: f64) -> Vec3 {
+    let x = x.clamp_(0.0, 1.0);
+    Vec3::new(
+        (2.0 * x - 1.0).clamp_(0.0, 1.0),
+        1.0 - (2.0 * x - 1.0).abs(),
+        (1.0 - 2.0 * x).clamp_(0.0, 1.0),
+    )
+}
+
+/// Returns the range of distances from `origin` to the world bounding box
+fn distance_range(origin: &Vec3, world: &dyn Hittable) -> Option<(f64, f64)> {
+    let b_box = world.bounding_box(0.0, 1.0)?;
+    let closest = origin.zip_with3(b_box.minimum, b_box.maximum, |o, min, max| {
+        o.clamp_(min, max)
+    });
+    let farthest = origin.zip_with3(b_box.minimum, b_box.maximum, |o, min, max| {
+        if (o - min).abs() > (o - max).abs() {
+            min
+        } else {
+            max
+        }
+    });
+    Some(((closest - *origin).length(), (farthest - *origin).length()))
+}
+
+impl DebugView {
+    /// Returns the color of a view that shows the data of a hit
+    fn hit_color(&self, r: &Ray, scene: &SceneContext, hit: &HitRecord) -> Vec3 {
+        match self {
+            Self::BVHCost { .. } => unreachable!("the BVH cost does not use the hit"),
+            Self::Normals => {
+                let outward_normal = if hit.front_face {
+                    hit.normal
+                } else {
+                    -hit.normal
+                };
+                0.5 * (outward_normal.unit_vector() + Vec3::one())
+            }
+            Self::UV => Vec3::new(hit.u, hit.v, 0.0),
+            Self::Distance => {
+                let distance = hit.t * r.direction.length();
+                match distance_range(&r.origin, scene.world) {
+                    Some((near, far)) if far > near => heatmap((distance - near) / (far - near)),
+                    _ => heatmap(0.0),
+                }
+            }
+            Self::FrontFace => {
+                if hit.front_face {
+                    Vec3::new(0.0, 1.0, 0.0)
+                } else {
+                    Vec3::new(1.0, 0.0, 0.0)
+                }
+            }
+            Self::MaterialId => {
+                let mut hasher = DefaultHasher::new();
+                (hit.material as *const dyn Material as *const u8).hash(&mut hasher);
+                let h = hasher.finish();
+                Vec3::new(
+                    (h & 0xff) as f64,
+                    ((h >> 8) & 0xff) as f64,
+                    ((h >> 16) & 0xff) as f64,
+                ) / 255.0
+            }
+        }
+    }
+}
+
+impl Integrator for DebugView {
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3 {
+        let color = match self {
+            // Counts the visits itself, without tracing the ray first
+            Self::BVHCost { max_cost } => {
+                let visits = scene.world.node_visits(r, HIT_T_MIN, f64::INFINITY);
+                heatmap(visits as f64 / *max_cost as f64)
+            }
+            _ => scene
+                .hit(r)
+                .map_or(Vec3::zero(), |hit| self.hit_color(r, scene, &hit)),
+        };
+
+        // The image is written with gamma 2, square it to show the exact color
+        color * color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bvh::BVH;
+    use crate::camera::CameraConfig;
+    use crate::material::Lambertian;
+    use crate::object::{get_sphere_uv, Sphere};
+
+    /// Color of `view` for a ray from `origin` along `direction` in a world
+    /// with a sphere of radius `1.0` at the origin
+    fn view_color(view: &DebugView, origin: Vec3, direction: Vec3) -> Vec3 {
+        let objects: Vec<Box<dyn Hittable>> = vec![Box::new(Sphere {
+            center: Vec3::zero(),
+            radius: 1.0,
+            material: Lambertian::from_color(Vec3::one()),
+        })];
+        let world = BVH::build(objects, 0.0, 1.0);
+        let camera = Camera::new(
+            &CameraConfig {
+                lookfrom: Vec3::new(0.0, 0.0, 5.0),
+                lookat: Vec3::zero(),
+                vup: Vec3::new(0.0, 1.0, 0.0),
+                vfov: 50.0,
+                aperture: 0.0,
+                focus_distance: 1.0,
+                time0: 0.0,
+                time1: 1.0,
+            },
+            1.0,
+        );
+        let scene = SceneContext {
+            world: &world,
+            lights: &world,
+            delta_lights: &[],
+            camera: &camera,
+            film: &Film::new(1, 1),
+            environment: &EnvironmentLight::from_color(Vec3::zero()),
+            max_bounces: 1,
+        };
+        view.radiance(&Ray::new(origin, direction, 0.0), &scene)
+    }
+
+    /// Color of `view` for a ray that hits the sphere at `(0, 0, 1)` from outside
+    fn front_color(view: &DebugView) -> Vec3 {
+        view_color(view, Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0))
+    }
+
+    #[test]
+    fn views_of_a_sphere() {
+        let squared = |c: Vec3| c * c;
+        assert_eq!(
+            front_color(&DebugView::Normals),
+            squared(Vec3::new(0.5, 0.5, 1.0))
+        );
+        let (u, v) = get_sphere_uv(&Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(front_color(&DebugView::UV), squared(Vec3::new(u, v, 0.0)));
+        // The hit is the closest point of the bounding box
+        assert_eq!(front_color(&DebugView::Distance), heatmap(0.0));
+        assert_eq!(front_color(&DebugView::FrontFace), Vec3::new(0.0, 1.0, 0.0));
+        let inside = view_color(
+            &DebugView::FrontFace,
+            Vec3::zero(),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert_eq!(inside, Vec3::new(1.0, 0.0, 0.0));
+        // The outward normal does not depend on the face
+        let inside = view_color(&DebugView::Normals, Vec3::zero(), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(inside, squared(Vec3::new(0.5, 0.5, 0.0)));
+    }
+
+    #[test]
+    fn material_id_is_the_same_for_a_material() {
+        let id = front_color(&DebugView::MaterialId);
+        let other = view_color(
+            &DebugView::MaterialId,
+            Vec3::new(5.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+        );
+        assert_eq!(id, other);
+        assert!(id.iter().all(|c| (0.0..=1.0).contains(c)));
+    }
+
+    #[test]
+    fn bvh_cost_counts_visits() {
+        // The root of a single object is also its leaf
+        let view = DebugView::BVHCost { max_cost: 2 };
+        assert_eq!(front_color(&view), Vec3::new(0.0, 1.0, 0.0));
+        let miss = view_color(&view, Vec3::new(0.0, 5.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(miss, Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn misses_are_black() {
+        for name in get_debug_views().iter().filter(|name| **name != "bvh_cost") {
+            let view = DebugView::from_name(name).unwrap();
+            let color = view_color(&view, Vec3::new(0.0, 5.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+            assert_eq!(color, Vec3::zero(), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use super::*;
+
+/// Direct lighting only
+///
+/// Follows specular bounces until a non specular surface is hit and
+/// there only the light arriving directly from emitters and the
+/// environment is computed. There is no indirect light, so it's fast
+/// but shadows are completely dark.
+#[derive(Clone)]
+pub struct DirectLighting;
+
+impl Integrator for DirectLighting {
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3 {
+        let mut radiance = Vec3::zero();
+        let mut throughput = Vec3::one();
+        let mut ray = r.clone();
+
+        for _ in 0..scene.max_bounces {
+            let hit = match scene.hit(&ray) {
+                Some(hit) => hit,
+                None => {
+                    radiance += throughput * scene.environment.le(&ray);
+                    break;
+                }
+            };
+
+            // Only reached from the camera or specular bounces
+            radiance += throughput * hit.material.emitted(&hit);
+
+            let (attenuation, scattered) = match hit.material.scatter(&ray, &hit) {
+                Some(s) => s,
+                None => break,
+            };
+
+            if hit.material.is_specular(&hit) {
+                throughput *= attenuation;
+                ray = scattered;
+                continue;
+            }
+
+            // Light sampling
+            radiance += throughput * sample_lights(&ray, &hit, scene, true);
+            radiance += throughput * sample_environment(&ray, &hit, scene);
+
+            // Material sampling
+            let throughput = throughput * attenuation;
+            let pdf = hit
+                .material
+                .scattering_pdf(&ray, &hit, &scattered.direction);
+            match scene.hit(&scattered) {
+                Some(light_hit) => {
+                    let emitted = light_hit.material.emitted(&light_hit);
+                    if !emitted.near_zero() {
+                        let weight = power_heuristic(
+                            pdf,
+                            scene.lights.pdf_value(&hit.point, &scattered.direction),
+                        );
+                        radiance += throughput * emitted * weight;
+                    }
+                }
+                None => {
+                    let weight = environment_weight(scene, pdf, &scattered.direction);
+                    radiance += throughput * scene.environment.le(&scattered) * weight;
+                }
+            }
+            break;
+        }
+
+        radiance
+    }
+}
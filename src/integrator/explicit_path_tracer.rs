@@ -0,0 +1,71 @@
+use super::*;
+
+/// Path tracer with explicit light sampling
+///
+/// On every non specular hit a shadow ray is traced towards the scene
+/// lights (next event estimation). It is combined with the material
+/// sampling with multiple importance sampling, so small lights converge
+/// much faster than with the [PathTracer].
+#[derive(Clone)]
+pub struct ExplicitPathTracer;
+
+impl Integrator for ExplicitPathTracer {
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3 {
+        let mut radiance = Vec3::zero();
+        let mut throughput = Vec3::one();
+        let mut ray = r.clone();
+        // Camera rays and specular bounces cannot sample lights, so
+        // emitters hit by them take the full contribution
+        let mut specular_bounce = true;
+        let mut last_point = ray.origin;
+        let mut last_pdf = 0.0;
+
+        for _ in 0..scene.max_bounces {
+            let hit = match scene.hit(&ray) {
+                Some(hit) => hit,
+                None => {
+                    let weight = if specular_bounce {
+                        1.0
+                    } else {
+                        environment_weight(scene, last_pdf, &ray.direction)
+                    };
+                    radiance += throughput * scene.environment.le(&ray) * weight;
+                    break;
+                }
+            };
+
+            let emitted = hit.material.emitted(&hit);
+            if !emitted.near_zero() {
+                let weight = if specular_bounce {
+                    1.0
+                } else {
+                    power_heuristic(
+                        last_pdf,
+                        scene.lights.pdf_value(&last_point, &ray.direction),
+                    )
+                };
+                radiance += throughput * emitted * weight;
+            }
+
+            let (attenuation, scattered) = match hit.material.scatter(&ray, &hit) {
+                Some(s) => s,
+                None => break,
+            };
+
+            specular_bounce = hit.material.is_specular(&hit);
+            if !specular_bounce {
+                radiance += throughput * sample_lights(&ray, &hit, scene, true);
+                radiance += throughput * sample_environment(&ray, &hit, scene);
+                last_pdf = hit
+                    .material
+                    .scattering_pdf(&ray, &hit, &scattered.direction);
+                last_point = hit.point;
+            }
+
+            throughput *= attenuation;
+            ray = scattered;
+        }
+
+        radiance
+    }
+}
@@ -0,0 +1,298 @@
+//! Contains the light transport algorithms the renderer can use.
+//!
+//! Every algorithm implements the [Integrator] trait, which computes
+//! the light arriving through a ray. Some of them are faster and
+//! others converge to a more correct image. More algorithms can be
+//! created implementing the [Integrator] trait; the [sample_lights] and
+//! [power_heuristic] functions are exposed to be used if wanted.
+
+use crate::camera::Camera;
+use crate::film::Film;
+use crate::hittable::{HitRecord, Hittable};
+use crate::light::{EnvironmentLight, Light};
+use crate::ray::Ray;
+use rand::Rng;
+use vec3::Vec3;
+
+/// Minimum distance of a valid hit. It is not 0 because of float
+/// precission, not every ray will match exactly with 0.0
+pub const HIT_T_MIN: f64 = 0.001;
+
+/// Everything an [Integrator] needs to know about the scene being rendered
+pub struct SceneContext<'a> {
+    /// Every object in the scene
+    pub world: &'a dyn Hittable,
+    /// Objects that emit light and can be sampled explicitly
+    pub lights: &'a dyn Hittable,
+    /// Lights that are not objects, like point lights. They can only be
+    /// reached with shadow rays.
+    pub delta_lights: &'a [Box<dyn Light>],
+    /// Camera that generates the rays
+    pub camera: &'a Camera,
+    /// Where light that reaches other pixels than the one being computed
+    /// is added
+    pub film: &'a Film,
+    /// Light that arrives when nothing is hit
+    pub environment: &'a EnvironmentLight,
+    /// Maximum number of bounces of a path
+    pub max_bounces: u32,
+}
+
+impl<'a> SceneContext<'a> {
+    /// Returns the closest hit of the ray with the world
+    #[inline]
+    pub fn hit(&self, r: &Ray) -> Option<HitRecord<'a>> {
+        self.world.hit(r, HIT_T_MIN, f64::INFINITY)
+    }
+}
+
+/// The [Integrator] trait has to be implemented for every rendering
+/// algorithm.
+pub trait Integrator: Send + Sync {
+    /// Returns the light ([Vec3] interpreted as color) that arrives to
+    /// the origin of the ray `r` from its direction.
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3;
+    #[allow(unused)]
+    /// Called once before rendering, with up to `threads` threads available,
+    /// to build the data the integrator needs. Statistics are only printed
+    /// if `print_debug` is `true`. It has a default implementation that
+    /// does nothing.
+    fn preprocess(&mut self, scene: &SceneContext, threads: usize, print_debug: bool) {}
+}
+
+/// Power heuristic (with exponent 2) of multiple importance sampling. Returns
+/// the weight of a sample taken with a strategy of density `pdf_f` when
+/// another strategy with density `pdf_g` could also have taken it.
+pub fn power_heuristic(pdf_f: f64, pdf_g: f64) -> f64 {
+    let f = pdf_f * pdf_f;
+    let g = pdf_g * pdf_g;
+    if f + g == 0.0 {
+        0.0
+    } else {
+        f / (f + g)
+    }
+}
+
+/// Light sample taken by [sample_light_ray] or [sample_delta_light_ray]
+pub struct LightSample {
+    /// Scattering function of the hit towards the light
+    pub f: Vec3,
+    /// Light emitted by the light towards the hit
+    pub emitted: Vec3,
+    /// Multiple importance sampling weight divided by the pdf of the sample
+    pub weight: f64,
+}
+
+/// Traces a single shadow ray from a hit towards a random point of the
+/// lights. Returns [None] when the sample does not carry any light.
+///
+/// If `mis` is `true`, the weight of the sample includes the
+/// [power_heuristic] against sampling the material.
+pub fn sample_light_ray(
+    r_in: &Ray,
+    hit: &HitRecord,
+    scene: &SceneContext,
+    mis: bool,
+) -> Option<LightSample> {
+    let direction = scene.lights.random(&hit.point);
+    let light_pdf = scene.lights.pdf_value(&hit.point, &direction);
+    if light_pdf <= 0.0 {
+        return None;
+    }
+
+    let f = hit.material.eval(r_in, hit, &direction);
+    if f.near_zero() {
+        return None;
+    }
+
+    let shadow_ray = Ray::new(hit.point, direction, r_in.time);
+    let light_hit = scene.hit(&shadow_ray)?;
+    let emitted = light_hit.material.emitted(&light_hit);
+    let weight = if mis {
+        power_heuristic(
+            light_pdf,
+            hit.material.scattering_pdf(r_in, hit, &direction),
+        )
+    } else {
+        1.0
+    };
+    Some(LightSample {
+        f,
+        emitted,
+        weight: weight / light_pdf,
+    })
+}
+
+/// Estimates the light that arrives directly from the scene lights to a
+/// hit and is scattered back through `r_in`. A single shadow ray is traced
+/// towards a random point of the lights, and another one towards a random
+/// [delta light](SceneContext::delta_lights).
+///
+/// If `mis` is `true`, the light of the objects is weighted with the
+/// [power_heuristic] against sampling the material.
+pub fn sample_lights(r_in: &Ray, hit: &HitRecord, scene: &SceneContext, mis: bool) -> Vec3 {
+    let delta = sample_delta_lights(r_in, hit, scene);
+    match sample_light_ray(r_in, hit, scene, mis) {
+        Some(sample) => sample.f * sample.emitted * sample.weight + delta,
+        None => delta,
+    }
+}
+
+/// Traces a single shadow ray from a hit towards a random one of the
+/// [delta lights](SceneContext::delta_lights). Returns [None] when the
+/// sample does not carry any light. Materials cannot sample these lights,
+/// so there is no multiple importance sampling.
+pub fn sample_delta_light_ray(
+    r_in: &Ray,
+    hit: &HitRecord,
+    scene: &SceneContext,
+) -> Option<LightSample> {
+    if scene.delta_lights.is_empty() {
+        return None;
+    }
+    let count = scene.delta_lights.len();
+    let light = &scene.delta_lights[rand::thread_rng().gen_range(0..count)];
+    let illumination = light.illuminate(&hit.point)?;
+
+    let f = hit.material.eval(r_in, hit, &illumination.direction);
+    if f.near_zero() {
+        return None;
+    }
+    let shadow_ray = Ray::new(hit.point, illumination.direction, r_in.time);
+    let t_max = illumination.distance - HIT_T_MIN;
+    if scene.world.hit(&shadow_ray, HIT_T_MIN, t_max).is_some() {
+        return None;
+    }
+    Some(LightSample {
+        f,
+        emitted: illumination.radiance,
+        weight: count as f64,
+    })
+}
+
+/// Estimates the light that arrives from the [delta lights](SceneContext::delta_lights)
+/// to a hit and is scattered back through `r_in`, with a single shadow ray
+/// towards a random one of them.
+pub fn sample_delta_lights(r_in: &Ray, hit: &HitRecord, scene: &SceneContext) -> Vec3 {
+    match sample_delta_light_ray(r_in, hit, scene) {
+        Some(sample) => sample.f * sample.emitted * sample.weight,
+        None => Vec3::zero(),
+    }
+}
+
+/// Traces a single shadow ray from a hit towards a random direction of the
+/// [environment](SceneContext::environment). Returns [None] when the sample
+/// does not carry any light.
+///
+/// The weight of the sample includes the [power_heuristic] against
+/// sampling the material, so rays that escape after a non specular bounce
+/// have to be weighted with [environment_weight].
+pub fn sample_environment_ray(
+    r_in: &Ray,
+    hit: &HitRecord,
+    scene: &SceneContext,
+) -> Option<LightSample> {
+    let (direction, pdf) = scene.environment.sample()?;
+    let f = hit.material.eval(r_in, hit, &direction);
+    if f.near_zero() {
+        return None;
+    }
+    let shadow_ray = Ray::new(hit.point, direction, r_in.time);
+    if scene.hit(&shadow_ray).is_some() {
+        return None;
+    }
+    let weight = power_heuristic(pdf, hit.material.scattering_pdf(r_in, hit, &direction));
+    Some(LightSample {
+        f,
+        emitted: scene.environment.le(&shadow_ray),
+        weight: weight / pdf,
+    })
+}
+
+/// Estimates the light that arrives from the [environment](SceneContext::environment)
+/// to a hit and is scattered back through `r_in`, with a single shadow ray.
+/// See [sample_environment_ray].
+pub fn sample_environment(r_in: &Ray, hit: &HitRecord, scene: &SceneContext) -> Vec3 {
+    match sample_environment_ray(r_in, hit, scene) {
+        Some(sample) => sample.f * sample.emitted * sample.weight,
+        None => Vec3::zero(),
+    }
+}
+
+/// Multiple importance sampling weight of the environment seen by a ray in
+/// `direction` scattered by a non specular material with density `pdf`,
+/// against [sample_environment_ray]
+pub fn environment_weight(scene: &SceneContext, pdf: f64, direction: &Vec3) -> f64 {
+    power_heuristic(pdf, scene.environment.pdf(direction))
+}
+
+/// Returns the names of the available integrators
+pub fn get_integrators() -> [&'static str; 8] {
+    [
+        "path",
+        "explicit_path",
+        "spectral",
+        "bidirectional",
+        "photon_mapping",
+        "direct",
+        "whitted",
+        "ambient_occlusion",
+    ]
+}
+
+/// Builds an integrator with its default parameters from its name
+pub fn get_integrator_from_name(name: &str) -> Option<Box<dyn Integrator>> {
+    match name {
+        "path" => Some(Box::new(PathTracer)),
+        "explicit_path" => Some(Box::new(ExplicitPathTracer)),
+        "spectral" => Some(Box::new(SpectralPathTracer::default())),
+        "bidirectional" => Some(Box::new(BidirectionalPathTracer)),
+        "photon_mapping" => Some(Box::new(PhotonMapping::default())),
+        "direct" => Some(Box::new(DirectLighting)),
+        "whitted" => Some(Box::new(Whitted)),
+        "ambient_occlusion" => Some(Box::new(AmbientOcclusion::default())),
+        _ => None,
+    }
+}
+
+mod path_tracer;
+pub use path_tracer::*;
+mod explicit_path_tracer;
+pub use explicit_path_tracer::*;
+mod spectral;
+pub use spectral::*;
+mod bidirectional;
+pub use bidirectional::*;
+mod photon_mapping;
+pub use photon_mapping::*;
+mod direct_lighting;
+pub use direct_lighting::*;
+mod whitted;
+pub use whitted::*;
+mod ambient_occlusion;
+pub use ambient_occlusion::*;
+mod debug_view;
+pub use debug_view::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_heuristic_weights() {
+        assert_eq!(power_heuristic(1.0, 0.0), 1.0);
+        assert_eq!(power_heuristic(0.0, 1.0), 0.0);
+        assert_eq!(power_heuristic(0.0, 0.0), 0.0);
+        assert_eq!(power_heuristic(2.0, 2.0), 0.5);
+        let w = power_heuristic(1.0, 3.0) + power_heuristic(3.0, 1.0);
+        assert!((w - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn every_integrator_can_be_built() {
+        for name in get_integrators().iter() {
+            assert!(get_integrator_from_name(name).is_some());
+        }
+        assert!(get_integrator_from_name("unknown").is_none());
+    }
+}
@@ -0,0 +1,42 @@
+use super::*;
+
+/// Naive path tracer
+///
+/// Paths bounce following the material scattering until they hit
+/// something that emits light by chance. It's the algorithm of the
+/// book; unbiased but noisy when the lights are small. Delta lights,
+/// like a [PointLight](crate::light::PointLight), cannot be hit by chance,
+/// so they do not light anything.
+#[derive(Clone)]
+pub struct PathTracer;
+
+fn ray_color(r: &Ray, scene: &SceneContext, depth: u32) -> Vec3 {
+    // If maximum number of rays
+    if depth == 0 {
+        return Vec3::zero();
+    }
+
+    if let Some(hit) = scene.hit(r) {
+        // if hits something
+
+        // Calculate the light emitted
+        let emitted = hit.material.emitted(&hit);
+
+        if let Some((attenuation, scattered)) = hit.material.scatter(r, &hit) {
+            // if material scatters
+            emitted + attenuation * ray_color(&scattered, scene, depth - 1)
+        } else {
+            // if it not, only emits
+            emitted
+        }
+    } else {
+        // if hits nothing, the environment is visible
+        scene.environment.le(r)
+    }
+}
+
+impl Integrator for PathTracer {
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3 {
+        ray_color(r, scene, scene.max_bounces)
+    }
+}
@@ -0,0 +1,517 @@
+use super::*;
+use crate::aabb::AABB;
+use crate::onb::ONB;
+use rand::Rng;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f64::consts::{PI, TAU};
+
+/// Light particle traced from the scene lights
+#[derive(Clone)]
+pub struct Photon {
+    /// Point where the photon was stored
+    pub position: Vec3,
+    /// Direction the photon was travelling in
+    pub direction: Vec3,
+    /// Flux carried by the photon
+    pub power: Vec3,
+}
+
+/// kd-tree of [Photon]s
+///
+/// The tree is stored implicitly in an array: the node of the range
+/// `[lo, hi)` is the photon in the middle, and its children are the
+/// ranges on each side of it.
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    /// Axis used to split at each node
+    axes: Vec<usize>,
+}
+
+/// Distance to a photon, ordered by distance to use it in a [BinaryHeap]
+struct Neighbour(f64);
+
+impl PartialEq for Neighbour {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl Eq for Neighbour {}
+impl PartialOrd for Neighbour {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Neighbour {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PhotonMap {
+    /// Builds a balanced [PhotonMap] with the given photons
+    pub fn build(mut photons: Vec<Photon>) -> Self {
+        let mut axes = vec![0; photons.len()];
+        Self::build_node(&mut photons, &mut axes);
+        Self { photons, axes }
+    }
+
+    fn build_node(photons: &mut [Photon], axes: &mut [usize]) {
+        if photons.len() <= 1 {
+            return;
+        }
+
+        // Split by the axis where the photons are more spread
+        let mut min = Vec3::splat(f64::INFINITY);
+        let mut max = Vec3::splat(f64::NEG_INFINITY);
+        for photon in photons.iter() {
+            min = min.zip_with(photon.position, f64::min);
+            max = max.zip_with(photon.position, f64::max);
+        }
+        let extent = max - min;
+        let axis = (0..3)
+            .max_by(|&a, &b| extent[a].partial_cmp(&extent[b]).unwrap_or(Ordering::Equal))
+            .unwrap();
+
+        let mid = photons.len() / 2;
+        photons.select_nth_unstable_by(mid, |a, b| {
+            a.position[axis]
+                .partial_cmp(&b.position[axis])
+                .unwrap_or(Ordering::Equal)
+        });
+        axes[mid] = axis;
+
+        let (left, right) = photons.split_at_mut(mid);
+        let (left_axes, right_axes) = axes.split_at_mut(mid);
+        Self::build_node(left, left_axes);
+        Self::build_node(&mut right[1..], &mut right_axes[1..]);
+    }
+
+    /// Number of photons in the map
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    /// Returns `true` if there are no photons in the map
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    /// Calls `f` with every photon closer than `radius` to `point`
+    pub fn for_each_in_radius(&self, point: &Vec3, radius: f64, mut f: impl FnMut(&Photon)) {
+        self.visit_in_radius(0, self.photons.len(), point, radius * radius, &mut f);
+    }
+
+    fn visit_in_radius(
+        &self,
+        lo: usize,
+        hi: usize,
+        point: &Vec3,
+        radius_squared: f64,
+        f: &mut impl FnMut(&Photon),
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let photon = &self.photons[mid];
+        let axis = self.axes[mid];
+
+        if (photon.position - *point).length_squared() < radius_squared {
+            f(photon);
+        }
+
+        let delta = point[axis] - photon.position[axis];
+        let (near, far) = if delta < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.visit_in_radius(near.0, near.1, point, radius_squared, f);
+        if delta * delta < radius_squared {
+            self.visit_in_radius(far.0, far.1, point, radius_squared, f);
+        }
+    }
+
+    /// Returns the distance from `point` to its `k`-th nearest photon, or
+    /// [None] if there are less than `k` photons.
+    pub fn kth_nearest_distance(&self, point: &Vec3, k: usize) -> Option<f64> {
+        if k == 0 || self.photons.len() < k {
+            return None;
+        }
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        self.visit_nearest(0, self.photons.len(), point, k, &mut heap);
+        heap.peek().map(|n| n.0.sqrt())
+    }
+
+    fn visit_nearest(
+        &self,
+        lo: usize,
+        hi: usize,
+        point: &Vec3,
+        k: usize,
+        heap: &mut BinaryHeap<Neighbour>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let photon = &self.photons[mid];
+        let axis = self.axes[mid];
+
+        heap.push(Neighbour((photon.position - *point).length_squared()));
+        if heap.len() > k {
+            heap.pop();
+        }
+
+        let delta = point[axis] - photon.position[axis];
+        let (near, far) = if delta < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.visit_nearest(near.0, near.1, point, k, heap);
+        if heap.len() < k || delta * delta < heap.peek().unwrap().0 {
+            self.visit_nearest(far.0, far.1, point, k, heap);
+        }
+    }
+}
+
+/// Progressive photon mapping for caustics
+///
+/// Before rendering, photons are emitted from the scene lights and bounced
+/// through specular materials ([Dielectric](crate::material::Dielectric),
+/// [Metal](crate::material::Metal)...). When they reach a non specular
+/// surface after at least one specular bounce they are stored in a
+/// [PhotonMap]. The rest of the light is computed like in the
+/// [ExplicitPathTracer], and the caustics are gathered from the photons at
+/// every non specular hit.
+///
+/// Photons are traced in several passes, each one with a smaller gather
+/// radius than the previous, so the bias fades as more passes are used.
+pub struct PhotonMapping {
+    /// Number of photon maps built
+    pub passes: usize,
+    /// Number of photons emitted for each map
+    pub photons_per_pass: usize,
+    /// Fraction of photons kept between passes, in (`0.0`, `1.0`). Controls
+    /// how fast the radius shrinks.
+    pub alpha: f64,
+    /// Number of photons expected inside the initial gather radius
+    pub photons_in_radius: usize,
+    maps: Vec<PhotonMap>,
+    radii: Vec<f64>,
+}
+
+impl Default for PhotonMapping {
+    fn default() -> Self {
+        Self::new(16, 100_000)
+    }
+}
+
+impl PhotonMapping {
+    /// Creates a new [PhotonMapping] integrator that emits `photons_per_pass`
+    /// photons in each of the `passes`
+    pub fn new(passes: usize, photons_per_pass: usize) -> Self {
+        Self {
+            passes,
+            photons_per_pass,
+            alpha: 2.0 / 3.0,
+            photons_in_radius: 20,
+            maps: Vec::new(),
+            radii: Vec::new(),
+        }
+    }
+
+    /// Emits a photon from a random light of the scene, an object or a
+    /// delta light. Returns its ray and its power divided by the probability
+    /// density of emitting it.
+    fn emit_photon(&self, scene: &SceneContext, time: f64, bounds: &AABB) -> Option<(Ray, Vec3)> {
+        let surface = scene.lights.sample_surface(time);
+        // Half of the photons come from each kind of light, if there are both
+        let delta_probability = match (&surface, scene.delta_lights.len()) {
+            (_, 0) => 0.0,
+            (None, _) => 1.0,
+            (Some(_), _) => 0.5,
+        };
+
+        if rand::random::<f64>() < delta_probability {
+            let count = scene.delta_lights.len();
+            let light = &scene.delta_lights[rand::thread_rng().gen_range(0..count)];
+            let (ray, power) = light.emit(time, bounds)?;
+            return Some((ray, power * (count as f64 / delta_probability)));
+        }
+
+        let (light_hit, pdf_pos) = surface?;
+        // Lights may emit from both faces, so pick one of them
+        let side = if rand::random() {
+            light_hit.normal
+        } else {
+            -light_hit.normal
+        };
+        let direction = ONB::build_from_w(&side).local_vec(&Vec3::random_cosine_direction());
+        let le = light_hit.material.emitted(&light_hit.seen_from(&Ray::new(
+            light_hit.point,
+            -direction,
+            time,
+        )));
+        // The cosine cancels with the cosine weighted pdf
+        let power = le * (TAU / (pdf_pos * (1.0 - delta_probability)));
+        Some((Ray::new(light_hit.point, direction, time), power))
+    }
+
+    /// Traces `photons_per_pass` photons and builds a [PhotonMap] with the caustic ones
+    fn trace_pass(&self, scene: &SceneContext) -> PhotonMap {
+        let mut photons = Vec::new();
+        let scale = 1.0 / self.photons_per_pass as f64;
+        let bounds = scene.world.bounding_box(0.0, 1.0).unwrap_or(AABB {
+            minimum: Vec3::zero(),
+            maximum: Vec3::zero(),
+        });
+
+        for _ in 0..self.photons_per_pass {
+            let time = rand::random();
+            let (mut ray, mut power) = match self.emit_photon(scene, time, &bounds) {
+                Some(photon) => photon,
+                None if scene.delta_lights.is_empty() => break,
+                None => continue,
+            };
+            power *= scale;
+            let mut specular_bounces = 0;
+
+            for _ in 0..scene.max_bounces {
+                let hit = match scene.hit(&ray) {
+                    Some(hit) => hit,
+                    None => break,
+                };
+
+                if !hit.material.is_specular(&hit) {
+                    // Volumetric caustics are not supported
+                    if specular_bounces > 0 && !hit.material.is_volumetric() {
+                        photons.push(Photon {
+                            position: hit.point,
+                            direction: ray.direction.unit_vector(),
+                            power,
+                        });
+                    }
+                    break;
+                }
+
+                match hit.material.scatter(&ray, &hit) {
+                    Some((attenuation, scattered)) => {
+                        power *= attenuation;
+                        ray = scattered;
+                        specular_bounces += 1;
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        PhotonMap::build(photons)
+    }
+
+    /// Median distance to the `photons_in_radius`-th nearest neighbour of
+    /// some photons of the map
+    fn estimate_radius(&self, map: &PhotonMap) -> Option<f64> {
+        let step = (map.len() / 100).max(1);
+        let mut distances: Vec<f64> = map
+            .photons
+            .iter()
+            .step_by(step)
+            .filter_map(|photon| map.kth_nearest_distance(&photon.position, self.photons_in_radius))
+            .collect();
+        if distances.is_empty() {
+            return None;
+        }
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        Some(distances[distances.len() / 2])
+    }
+
+    /// Light of the caustics scattered from the photons around the hit
+    /// through `r_in`
+    fn caustics(&self, r_in: &Ray, hit: &HitRecord) -> Vec3 {
+        if self.maps.is_empty() || hit.material.is_volumetric() {
+            return Vec3::zero();
+        }
+        let pass = rand::random::<usize>() % self.maps.len();
+        let radius = self.radii[pass];
+
+        let mut flux = Vec3::zero();
+        self.maps[pass].for_each_in_radius(&hit.point, radius, |photon| {
+            let direction = -photon.direction;
+            let cosine = hit.normal.dot(&direction);
+            if cosine > 0.0 {
+                flux += hit.material.eval(r_in, hit, &direction) * photon.power / cosine;
+            }
+        });
+
+        flux / (PI * radius * radius)
+    }
+}
+
+impl Integrator for PhotonMapping {
+    fn preprocess(&mut self, scene: &SceneContext, threads: usize, print_debug: bool) {
+        if print_debug {
+            eprintln!(
+                "Tracing {} photons in {} passes...",
+                self.passes * self.photons_per_pass,
+                self.passes
+            );
+        }
+
+        let this = &*self;
+        let threads = threads.max(1).min(self.passes.max(1));
+        let maps: Vec<PhotonMap> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..threads)
+                .map(|t| {
+                    s.spawn(move || {
+                        (t..this.passes)
+                            .step_by(threads)
+                            .map(|_| this.trace_pass(scene))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("Photon tracing thread panicked"))
+                .collect()
+        });
+
+        // Radius of each pass: r_{i+1}^2 = r_i^2 (i + alpha) / (i + 1)
+        let initial_radius = maps
+            .iter()
+            .find_map(|map| self.estimate_radius(map))
+            .unwrap_or(1.0);
+        let mut radius_squared = initial_radius * initial_radius;
+        let mut radii = Vec::with_capacity(maps.len());
+        for i in 0..maps.len() {
+            radii.push(radius_squared.sqrt());
+            radius_squared *= (i as f64 + 1.0 + self.alpha) / (i as f64 + 2.0);
+        }
+
+        if print_debug {
+            eprintln!(
+                "Stored {} caustic photons, initial radius {:.4}",
+                maps.iter().map(PhotonMap::len).sum::<usize>(),
+                initial_radius
+            );
+        }
+        self.maps = maps;
+        self.radii = radii;
+    }
+
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3 {
+        let mut radiance = Vec3::zero();
+        let mut throughput = Vec3::one();
+        let mut ray = r.clone();
+        // Camera rays and specular bounces cannot sample lights, so
+        // emitters hit by them take the full contribution
+        let mut specular_bounce = true;
+        // Once a non specular surface is found, specular paths to the
+        // lights are caustics and the photons already account for them
+        let mut after_diffuse = false;
+        let mut last_point = ray.origin;
+        let mut last_pdf = 0.0;
+
+        for _ in 0..scene.max_bounces {
+            let hit = match scene.hit(&ray) {
+                Some(hit) => hit,
+                None => {
+                    let weight = if specular_bounce {
+                        1.0
+                    } else {
+                        environment_weight(scene, last_pdf, &ray.direction)
+                    };
+                    radiance += throughput * scene.environment.le(&ray) * weight;
+                    break;
+                }
+            };
+
+            let emitted = hit.material.emitted(&hit);
+            if !emitted.near_zero() {
+                let weight = if !specular_bounce {
+                    power_heuristic(
+                        last_pdf,
+                        scene.lights.pdf_value(&last_point, &ray.direction),
+                    )
+                } else if after_diffuse {
+                    0.0
+                } else {
+                    1.0
+                };
+                radiance += throughput * emitted * weight;
+            }
+
+            let (attenuation, scattered) = match hit.material.scatter(&ray, &hit) {
+                Some(s) => s,
+                None => break,
+            };
+
+            specular_bounce = hit.material.is_specular(&hit);
+            if !specular_bounce {
+                radiance += throughput * sample_lights(&ray, &hit, scene, true);
+                radiance += throughput * sample_environment(&ray, &hit, scene);
+                radiance += throughput * self.caustics(&ray, &hit);
+                after_diffuse = true;
+                last_pdf = hit
+                    .material
+                    .scattering_pdf(&ray, &hit, &scattered.direction);
+                last_point = hit.point;
+            }
+
+            throughput *= attenuation;
+            ray = scattered;
+        }
+
+        radiance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_photons(n: usize) -> Vec<Photon> {
+        (0..n)
+            .map(|_| Photon {
+                position: Vec3::random_in_range(-10.0, 10.0),
+                direction: Vec3::new(0.0, -1.0, 0.0),
+                power: Vec3::one(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn radius_query_finds_every_photon_inside() {
+        let photons = random_photons(1000);
+        let point = Vec3::new(1.0, 2.0, -3.0);
+        let radius = 4.0;
+        let expected = photons
+            .iter()
+            .filter(|p| (p.position - point).length() < radius)
+            .count();
+
+        let map = PhotonMap::build(photons);
+        let mut found = 0;
+        map.for_each_in_radius(&point, radius, |_| found += 1);
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn kth_nearest_matches_sorted_distances() {
+        let photons = random_photons(500);
+        let point = Vec3::zero();
+        let mut distances: Vec<f64> = photons
+            .iter()
+            .map(|p| (p.position - point).length())
+            .collect();
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let map = PhotonMap::build(photons);
+        let d = map.kth_nearest_distance(&point, 10).unwrap();
+        assert!((d - distances[9]).abs() < 1e-9);
+        assert!(map.kth_nearest_distance(&point, 501).is_none());
+    }
+}
@@ -0,0 +1,92 @@
+use super::*;
+use crate::spectrum::{rgb_to_spectrum, SpectralResponse, LAMBDA_MAX, LAMBDA_MIN};
+
+/// Spectral path tracer with explicit light sampling
+///
+/// Works like the [ExplicitPathTracer] but every path carries a single
+/// random wavelength instead of an RGB color. The colors of the scene are
+/// upsampled to spectra at that wavelength and the result is converted to
+/// RGB through the CIE color matching functions. Materials whose behaviour
+/// depends on the wavelength, like a dispersive [Dielectric](crate::material::Dielectric),
+/// split white light into its colors.
+#[derive(Clone, Default)]
+pub struct SpectralPathTracer {
+    response: SpectralResponse,
+}
+
+impl Integrator for SpectralPathTracer {
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3 {
+        let wavelength = LAMBDA_MIN + rand::random::<f64>() * (LAMBDA_MAX - LAMBDA_MIN);
+        let spectrum = |color: Vec3| rgb_to_spectrum(&color, wavelength);
+
+        let mut radiance = 0.0;
+        let mut throughput = 1.0;
+        let mut ray = r.clone();
+        ray.wavelength = Some(wavelength);
+        // Camera rays and specular bounces cannot sample lights, so
+        // emitters hit by them take the full contribution
+        let mut specular_bounce = true;
+        let mut last_point = ray.origin;
+        let mut last_pdf = 0.0;
+
+        for _ in 0..scene.max_bounces {
+            let hit = match scene.hit(&ray) {
+                Some(hit) => hit,
+                None => {
+                    let weight = if specular_bounce {
+                        1.0
+                    } else {
+                        environment_weight(scene, last_pdf, &ray.direction)
+                    };
+                    radiance += throughput * spectrum(scene.environment.le(&ray)) * weight;
+                    break;
+                }
+            };
+
+            let emitted = hit.material.emitted(&hit);
+            if !emitted.near_zero() {
+                let weight = if specular_bounce {
+                    1.0
+                } else {
+                    power_heuristic(
+                        last_pdf,
+                        scene.lights.pdf_value(&last_point, &ray.direction),
+                    )
+                };
+                radiance += throughput * spectrum(emitted) * weight;
+            }
+
+            let (attenuation, mut scattered) = match hit.material.scatter(&ray, &hit) {
+                Some(s) => s,
+                None => break,
+            };
+
+            specular_bounce = hit.material.is_specular(&hit);
+            if !specular_bounce {
+                if let Some(sample) = sample_light_ray(&ray, &hit, scene, true) {
+                    radiance +=
+                        throughput * spectrum(sample.f) * spectrum(sample.emitted) * sample.weight;
+                }
+                if let Some(sample) = sample_environment_ray(&ray, &hit, scene) {
+                    radiance +=
+                        throughput * spectrum(sample.f) * spectrum(sample.emitted) * sample.weight;
+                }
+                if let Some(sample) = sample_delta_light_ray(&ray, &hit, scene) {
+                    radiance +=
+                        throughput * spectrum(sample.f) * spectrum(sample.emitted) * sample.weight;
+                }
+                last_pdf = hit
+                    .material
+                    .scattering_pdf(&ray, &hit, &scattered.direction);
+                last_point = hit.point;
+            }
+
+            throughput *= spectrum(attenuation);
+            scattered.wavelength = ray.wavelength;
+            ray = scattered;
+        }
+
+        let pdf = 1.0 / (LAMBDA_MAX - LAMBDA_MIN);
+        self.response.to_rgb(radiance, wavelength, pdf)
+    }
+}
@@ -0,0 +1,45 @@
+use super::*;
+
+/// Whitted-style ray tracer
+///
+/// Specular materials are followed recursively while non specular ones
+/// only receive light with a shadow ray towards the lights and, as an
+/// ambient term, from the environment. Fast, but without indirect
+/// diffuse light.
+#[derive(Clone)]
+pub struct Whitted;
+
+fn trace(r: &Ray, scene: &SceneContext, depth: u32) -> Vec3 {
+    if depth == 0 {
+        return Vec3::zero();
+    }
+
+    let hit = match scene.hit(r) {
+        Some(hit) => hit,
+        None => return scene.environment.le(r),
+    };
+
+    let emitted = hit.material.emitted(&hit);
+
+    let (attenuation, scattered) = match hit.material.scatter(r, &hit) {
+        Some(s) => s,
+        None => return emitted,
+    };
+
+    if hit.material.is_specular(&hit) {
+        emitted + attenuation * trace(&scattered, scene, depth - 1)
+    } else {
+        let ambient = if scene.hit(&scattered).is_none() {
+            attenuation * scene.environment.le(&scattered)
+        } else {
+            Vec3::zero()
+        };
+        emitted + sample_lights(r, &hit, scene, false) + ambient
+    }
+}
+
+impl Integrator for Whitted {
+    fn radiance(&self, r: &Ray, scene: &SceneContext) -> Vec3 {
+        trace(r, scene, scene.max_bounces)
+    }
+}
@@ -8,13 +8,19 @@ pub mod aabb;
 pub mod bvh;
 pub mod camera;
 pub mod constant_medium;
+pub mod film;
 pub mod hittable;
 pub mod image_helper;
+pub mod integrator;
+pub mod light;
 pub mod material;
 pub mod object;
+pub mod onb;
 pub mod ray;
 pub mod render;
 pub mod scenes;
+pub mod spectrum;
+pub mod subsurface;
 pub mod texture;
 
 /// This trait attaches the [`clamp_`](Self::clamp_) method to a type.
@@ -0,0 +1,56 @@
+use super::*;
+use std::f64::consts::{PI, TAU};
+
+/// Light that arrives from a direction, infinitely far away
+///
+/// Like the sun: every point receives the same light with parallel rays,
+/// so it does not fall off with distance.
+#[derive(Clone)]
+pub struct DirectionalLight {
+    /// Direction the light travels in, from the light to the scene
+    pub direction: Vec3,
+    /// Color of the light
+    pub color: Vec3,
+    /// Multiplies the color. It is the irradiance (watts per square meter)
+    /// of a white light on a surface facing it.
+    pub intensity: f64,
+}
+
+impl DirectionalLight {
+    /// Creates a new [DirectionalLight] of the given color
+    pub fn new(direction: Vec3, color: Vec3) -> Self {
+        Self {
+            direction,
+            color,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn illuminate(&self, _point: &Vec3) -> Option<Illumination> {
+        Some(Illumination {
+            direction: -self.direction.unit_vector(),
+            distance: f64::INFINITY,
+            radiance: self.color * self.intensity,
+        })
+    }
+
+    fn emit(&self, time: f64, bounds: &AABB) -> Option<(Ray, Vec3)> {
+        // Rays start from a disk as big as the scene, facing the light,
+        // outside the bounding sphere of the scene
+        let center = 0.5 * (bounds.minimum + bounds.maximum);
+        let radius = 0.5 * (bounds.maximum - bounds.minimum).length();
+        if !radius.is_finite() || radius <= 0.0 {
+            return None;
+        }
+        let direction = self.direction.unit_vector();
+        let onb = ONB::build_from_w(&direction);
+        let r = radius * rand::random::<f64>().sqrt();
+        let phi = TAU * rand::random::<f64>();
+        let origin = center - radius * direction + r * (phi.cos() * onb.u + phi.sin() * onb.v);
+        let area = PI * radius * radius;
+        let power = self.color * (self.intensity * area);
+        Some((Ray::new(origin, direction, time), power))
+    }
+}
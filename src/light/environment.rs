@@ -0,0 +1,304 @@
+use super::*;
+use crate::object::get_sphere_uv;
+use crate::texture::{ImageTexture, ImageTextureError, SolidColor, Texture, WrapMode};
+use std::f64::consts::{PI, TAU};
+use std::sync::Arc;
+
+/// Piecewise constant distribution of a variable in [`0.0`, `1.0`]
+#[derive(Clone)]
+struct Distribution1D {
+    func: Vec<f64>,
+    /// Cumulative distribution at the start of each piece, and `1.0`
+    cdf: Vec<f64>,
+    /// Average of the function
+    integral: f64,
+}
+
+impl Distribution1D {
+    /// Creates a distribution proportional to the pieces of `func`, of the
+    /// same size. Returns [None] if the function is zero everywhere.
+    fn new(func: Vec<f64>) -> Option<Self> {
+        let n = func.len() as f64;
+        let mut cdf = Vec::with_capacity(func.len() + 1);
+        let mut sum = 0.0;
+        cdf.push(0.0);
+        for f in &func {
+            sum += f / n;
+            cdf.push(sum);
+        }
+        if sum <= 0.0 {
+            return None;
+        }
+        for c in cdf.iter_mut() {
+            *c /= sum;
+        }
+        Some(Self {
+            func,
+            cdf,
+            integral: sum,
+        })
+    }
+
+    /// Maps a uniform random number `xi` to the distribution. Returns the
+    /// value, its piece and its probability density.
+    fn sample(&self, xi: f64) -> (f64, usize, f64) {
+        let i = (self.cdf.partition_point(|&c| c <= xi) - 1).min(self.func.len() - 1);
+        let width = self.cdf[i + 1] - self.cdf[i];
+        let offset = if width > 0.0 {
+            (xi - self.cdf[i]) / width
+        } else {
+            0.0
+        };
+        let x = (i as f64 + offset) / self.func.len() as f64;
+        (x, i, self.func[i] / self.integral)
+    }
+
+    /// Piece of the value `x`
+    fn piece(&self, x: f64) -> usize {
+        ((x * self.func.len() as f64) as usize).min(self.func.len() - 1)
+    }
+
+    /// Probability density of the value `x`
+    fn pdf(&self, x: f64) -> f64 {
+        self.func[self.piece(x)] / self.integral
+    }
+}
+
+/// Piecewise constant distribution of a point of [`0.0`, `1.0`]²
+#[derive(Clone)]
+struct Distribution2D {
+    /// Distribution of `u` for each row of `v`
+    conditional: Vec<Distribution1D>,
+    /// Distribution of `v`
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// Creates a distribution proportional to a grid of `width` by `height`
+    /// pieces, given by rows. Returns [None] if it is zero everywhere.
+    fn new(func: &[f64], width: usize, height: usize) -> Option<Self> {
+        let mut conditional = Vec::with_capacity(height);
+        let mut marginal = Vec::with_capacity(height);
+        for row in func.chunks(width).take(height) {
+            let sum: f64 = row.iter().sum();
+            marginal.push(sum / width as f64);
+            // Rows without weight are never chosen, any distribution works
+            conditional.push(
+                Distribution1D::new(row.to_vec())
+                    .unwrap_or_else(|| Distribution1D::new(vec![1.0]).unwrap()),
+            );
+        }
+        Some(Self {
+            conditional,
+            marginal: Distribution1D::new(marginal)?,
+        })
+    }
+
+    /// Returns a random point (`u`, `v`) and its probability density
+    fn sample(&self) -> (f64, f64, f64) {
+        let (v, row, pdf_v) = self.marginal.sample(rand::random());
+        let (u, _, pdf_u) = self.conditional[row].sample(rand::random());
+        (u, v, pdf_u * pdf_v)
+    }
+
+    /// Probability density of the point (`u`, `v`)
+    fn pdf(&self, u: f64, v: f64) -> f64 {
+        self.marginal.pdf(v) * self.conditional[self.marginal.piece(v)].pdf(u)
+    }
+}
+
+/// How an [EnvironmentLight] chooses the directions to sample
+#[derive(Clone)]
+enum Sampling {
+    /// The environment is black, it is not sampled
+    Black,
+    /// Every direction is equally likely
+    Uniform,
+    /// Directions are chosen proportionally to the luminance of the map
+    Luminance(Distribution2D),
+}
+
+/// Light that surrounds the scene, infinitely far away
+///
+/// Rays that hit nothing see it, and it lights the scene from every
+/// direction. It is a single color, like a plain sky, or an
+/// equirectangular image: a panorama with the longitude along `u` and the
+/// latitude along `v`, with the same mapping as a [Sphere](crate::object::Sphere).
+/// A high dynamic range image (like Radiance HDR or OpenEXR) captures the
+/// real light of a place, sun included.
+///
+/// The brightest parts of an image are sampled more often with shadow rays,
+/// so small and bright light sources in it converge quickly.
+#[derive(Clone)]
+pub struct EnvironmentLight {
+    texture: Arc<dyn Texture>,
+    /// Rotation around the vertical axis, in radians
+    rotation: f64,
+    intensity: f64,
+    sampling: Sampling,
+}
+
+/// Unit direction of the environment with coordinates (`u`, `v`), inverse
+/// of [get_sphere_uv]
+fn uv_to_direction(u: f64, v: f64) -> Vec3 {
+    let theta = v * PI;
+    let phi = u * TAU - PI;
+    let (sin_theta, cos_theta) = theta.sin_cos();
+    Vec3::new(phi.cos() * sin_theta, -cos_theta, -phi.sin() * sin_theta)
+}
+
+/// Rotates a direction around the vertical axis
+fn rotate_y(direction: &Vec3, angle: f64) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    Vec3::new(
+        cos * direction.x() + sin * direction.z(),
+        direction.y(),
+        -sin * direction.x() + cos * direction.z(),
+    )
+}
+
+impl EnvironmentLight {
+    /// Creates an environment of a single color
+    pub fn from_color(color: Vec3) -> Self {
+        Self {
+            texture: Arc::new(SolidColor { color }),
+            rotation: 0.0,
+            intensity: 1.0,
+            sampling: if color.near_zero() {
+                Sampling::Black
+            } else {
+                Sampling::Uniform
+            },
+        }
+    }
+
+    /// Creates an environment from the equirectangular image of the given path
+    pub fn new(filename: &str) -> Result<Self, ImageTextureError> {
+        Ok(Self::from_image(ImageTexture::new(filename)?))
+    }
+
+    /// Creates an environment from an equirectangular image
+    pub fn from_image(image: ImageTexture) -> Self {
+        let image = image.with_wrap(WrapMode::Repeat);
+        let (width, height) = image.dimensions();
+        // The rows near the poles cover less solid angle
+        let mut func = Vec::with_capacity(width * height);
+        for j in 0..height {
+            let v = (j as f64 + 0.5) / height as f64;
+            let sin_theta = (v * PI).sin();
+            for i in 0..width {
+                let u = (i as f64 + 0.5) / width as f64;
+                let color = image.value(u, v, &Vec3::zero());
+                func.push(color.luminance().max(0.0) * sin_theta);
+            }
+        }
+        let sampling = match Distribution2D::new(&func, width, height) {
+            Some(distribution) => Sampling::Luminance(distribution),
+            None => Sampling::Black,
+        };
+        Self {
+            texture: Arc::new(image),
+            rotation: 0.0,
+            intensity: 1.0,
+            sampling,
+        }
+    }
+
+    /// Rotates the environment `degrees` around the vertical axis
+    pub fn with_rotation(self, degrees: f64) -> Self {
+        Self {
+            rotation: degrees.to_radians(),
+            ..self
+        }
+    }
+
+    /// Sets the factor of the light of the environment
+    pub fn with_intensity(self, intensity: f64) -> Self {
+        Self { intensity, ..self }
+    }
+
+    /// Light arriving from the environment to the origin of a ray that
+    /// hits nothing
+    pub fn le(&self, r: &Ray) -> Vec3 {
+        if matches!(self.sampling, Sampling::Black) {
+            return Vec3::zero();
+        }
+        let direction = rotate_y(&r.direction.unit_vector(), -self.rotation);
+        let (u, v) = get_sphere_uv(&direction);
+        // The spread of the ray is about its angular width
+        self.intensity * self.texture.filtered(u, v, &direction, r.spread / TAU)
+    }
+
+    /// Returns a random unit direction towards the environment and its
+    /// probability density (with respect to solid angle). Returns [None]
+    /// if the environment gives no light.
+    pub fn sample(&self) -> Option<(Vec3, f64)> {
+        if self.intensity <= 0.0 {
+            return None;
+        }
+        match &self.sampling {
+            Sampling::Black => None,
+            Sampling::Uniform => Some((Vec3::random_unit_vector(), 1.0 / (4.0 * PI))),
+            Sampling::Luminance(distribution) => {
+                let (u, v, pdf) = distribution.sample();
+                let sin_theta = (v * PI).sin();
+                if sin_theta <= 0.0 {
+                    return None;
+                }
+                let direction = rotate_y(&uv_to_direction(u, v), self.rotation);
+                Some((direction, pdf / (2.0 * PI * PI * sin_theta)))
+            }
+        }
+    }
+
+    /// Probability density (with respect to solid angle) of
+    /// [sample](EnvironmentLight::sample) choosing `direction`
+    pub fn pdf(&self, direction: &Vec3) -> f64 {
+        if self.intensity <= 0.0 {
+            return 0.0;
+        }
+        match &self.sampling {
+            Sampling::Black => 0.0,
+            Sampling::Uniform => 1.0 / (4.0 * PI),
+            Sampling::Luminance(distribution) => {
+                let direction = rotate_y(&direction.unit_vector(), -self.rotation);
+                let (u, v) = get_sphere_uv(&direction);
+                let sin_theta = (v * PI).sin();
+                if sin_theta <= 0.0 {
+                    return 0.0;
+                }
+                distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uv_directions() {
+        for (u, v) in [(0.1, 0.3), (0.5, 0.5), (0.8, 0.9)] {
+            let (u2, v2) = get_sphere_uv(&uv_to_direction(u, v));
+            assert!((u - u2).abs() < 1e-9 && (v - v2).abs() < 1e-9);
+        }
+        let rotated = rotate_y(&Vec3::new(1.0, 0.0, 0.0), PI / 2.0);
+        assert!((rotated - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn distribution_sampling() {
+        // A single bright texel takes 3/4 of the samples
+        let distribution = Distribution2D::new(&[0.0, 3.0, 0.5, 0.5], 2, 2).unwrap();
+        assert_eq!(distribution.pdf(0.75, 0.25), 3.0);
+        assert_eq!(distribution.pdf(0.25, 0.25), 0.0);
+        assert_eq!(distribution.pdf(0.25, 0.75), 0.5);
+        for _ in 0..100 {
+            let (u, v, pdf) = distribution.sample();
+            assert_eq!(pdf, distribution.pdf(u, v));
+            assert!(pdf > 0.0);
+        }
+        assert!(Distribution2D::new(&[0.0; 4], 2, 2).is_none());
+    }
+}
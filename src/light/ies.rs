@@ -0,0 +1,199 @@
+use std::path::Path;
+
+/// Error that [IesProfile::from_file] can return
+#[derive(Debug)]
+pub enum IesError {
+    /// Error opening or reading the file.
+    IOError(std::io::Error),
+    /// The file is not a valid IES profile. Has the reason.
+    ParseError(String),
+}
+
+impl From<std::io::Error> for IesError {
+    fn from(cause: std::io::Error) -> Self {
+        IesError::IOError(cause)
+    }
+}
+
+/// Photometric profile of a real light, in the IES LM-63 format
+///
+/// Tells how much light a luminaire emits in every direction, as measured
+/// by its manufacturer. Directions are given by a vertical angle from the
+/// nadir (the light pointing down) and a horizontal angle around it, both
+/// in degrees. Only type C photometry, by far the most common, is read.
+#[derive(Clone, Debug)]
+pub struct IesProfile {
+    vertical_angles: Vec<f64>,
+    horizontal_angles: Vec<f64>,
+    /// Intensities for each horizontal angle, for each vertical angle,
+    /// relative to the highest one
+    intensities: Vec<Vec<f64>>,
+}
+
+impl IesProfile {
+    /// Reads the profile from an IES file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, IesError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Reads the profile from the contents of an IES file
+    pub fn parse(contents: &str) -> Result<Self, IesError> {
+        let error = |reason: &str| IesError::ParseError(reason.to_string());
+
+        // Skip the header and keywords, until the TILT line
+        let mut lines = contents.lines();
+        let tilt = lines
+            .by_ref()
+            .map(str::trim)
+            .find_map(|line| line.strip_prefix("TILT="))
+            .ok_or_else(|| error("missing TILT line"))?;
+        let mut numbers = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                token
+                    .parse::<f64>()
+                    .map_err(|_| error(&format!("invalid number {}", token)))
+            });
+        let mut next = || {
+            numbers
+                .next()
+                .unwrap_or_else(|| Err(error("unexpected end")))
+        };
+
+        // The tilt of the lamp changes little, it is skipped
+        if tilt.trim() == "INCLUDE" {
+            let _geometry = next()?;
+            let pairs = next()? as usize;
+            for _ in 0..2 * pairs {
+                next()?;
+            }
+        }
+
+        let _lamps = next()?;
+        let _lumens = next()?;
+        let multiplier = next()?;
+        let vertical_count = next()? as usize;
+        let horizontal_count = next()? as usize;
+        let photometric_type = next()?;
+        if photometric_type != 1.0 {
+            return Err(error("only type C photometry is supported"));
+        }
+        // Units and size of the luminaire, ballast, future use and watts
+        for _ in 0..7 {
+            next()?;
+        }
+
+        if vertical_count == 0 || horizontal_count == 0 {
+            return Err(error("no angles"));
+        }
+        let vertical_angles = (0..vertical_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let horizontal_angles = (0..horizontal_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut intensities = (0..horizontal_count)
+            .map(|_| {
+                (0..vertical_count)
+                    .map(|_| next().map(|c| c * multiplier))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let max = intensities
+            .iter()
+            .flatten()
+            .fold(0.0, |a: f64, &b| a.max(b));
+        if max > 0.0 {
+            for intensity in intensities.iter_mut().flatten() {
+                *intensity /= max;
+            }
+        }
+        Ok(Self {
+            vertical_angles,
+            horizontal_angles,
+            intensities,
+        })
+    }
+
+    /// Intensity in [`0.0`, `1.0`], relative to the highest of the profile,
+    /// in the direction of the given angles in degrees
+    pub fn intensity(&self, vertical: f64, horizontal: f64) -> f64 {
+        let first = self.vertical_angles[0];
+        let last = self.vertical_angles[self.vertical_angles.len() - 1];
+        if vertical < first || vertical > last {
+            return 0.0;
+        }
+
+        // The last horizontal angle tells the symmetry of the profile
+        let mut horizontal = horizontal.rem_euclid(360.0);
+        let symmetry = self.horizontal_angles[self.horizontal_angles.len() - 1];
+        if symmetry <= 180.0 && horizontal > 180.0 {
+            horizontal = 360.0 - horizontal;
+        }
+        if symmetry <= 90.0 && horizontal > 90.0 {
+            horizontal = 180.0 - horizontal;
+        }
+
+        let (h, th) = interpolation(&self.horizontal_angles, horizontal);
+        let (v, tv) = interpolation(&self.vertical_angles, vertical);
+        let at = |h: usize| {
+            let column = &self.intensities[h];
+            let next = column[(v + 1).min(column.len() - 1)];
+            column[v] + (next - column[v]) * tv
+        };
+        let next = (h + 1).min(self.horizontal_angles.len() - 1);
+        at(h) + (at(next) - at(h)) * th
+    }
+}
+
+/// Index of the last of the sorted `angles` not above `angle` and how far
+/// `angle` is to the next one, in [`0.0`, `1.0`]
+fn interpolation(angles: &[f64], angle: f64) -> (usize, f64) {
+    let i = angles
+        .partition_point(|&a| a <= angle)
+        .saturating_sub(1)
+        .min(angles.len() - 1);
+    match angles.get(i + 1) {
+        Some(next) if *next > angles[i] => (
+            i,
+            ((angle - angles[i]) / (next - angles[i])).clamp(0.0, 1.0),
+        ),
+        _ => (i, 0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: &str = "IESNA:LM-63-2002
+[TEST] Downlight
+[MANUFAC] Nobody
+TILT=NONE
+1 1000 2 3 3 1 2 0.1 0.1 0.0
+1.0 1.0 50
+0 45 90
+0 90 180
+100 50 0
+100 50 0
+50 25 0
+";
+
+    #[test]
+    fn parse_profile() {
+        let profile = IesProfile::parse(PROFILE).unwrap();
+        assert_eq!(profile.intensity(0.0, 0.0), 1.0);
+        assert_eq!(profile.intensity(22.5, 0.0), 0.75);
+        assert_eq!(profile.intensity(90.0, 0.0), 0.0);
+        assert_eq!(profile.intensity(135.0, 0.0), 0.0);
+        // Bilateral symmetry around the plane of 0 and 180 degrees
+        assert_eq!(profile.intensity(0.0, 135.0), 0.75);
+        assert_eq!(profile.intensity(0.0, 225.0), 0.75);
+        assert_eq!(profile.intensity(0.0, 180.0), 0.5);
+
+        assert!(IesProfile::parse("TILT=NONE\n1 1000").is_err());
+        assert!(IesProfile::parse("no tilt").is_err());
+    }
+}
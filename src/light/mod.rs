@@ -0,0 +1,63 @@
+//! Contains the lights that are not objects of the scene.
+//!
+//! Objects with an emissive material, like a [DiffuseLight](crate::material::DiffuseLight),
+//! are lights with an area that rays can hit. The lights of this module
+//! are points or directions instead (delta lights): rays never hit them,
+//! so they only reach the integrators that sample lights explicitly with
+//! shadow rays, and the [PathTracer](crate::integrator::PathTracer) does
+//! not see them. They give perfectly sharp shadows without noise.
+//!
+//! The [EnvironmentLight] surrounds the whole scene instead, and is seen
+//! by the rays that hit nothing.
+//!
+//! More lights can be created implementing the [Light] trait.
+
+use crate::aabb::AABB;
+use crate::onb::ONB;
+use crate::ray::Ray;
+use vec3::Vec3;
+
+/// Light that arrives to a point from a [Light]
+pub struct Illumination {
+    /// Unit direction from the point towards the light
+    pub direction: Vec3,
+    /// Distance to the light, infinite if it is infinitely far away
+    pub distance: f64,
+    /// Light arriving to the point, if nothing is in between
+    pub radiance: Vec3,
+}
+
+/// Trait that the lights that are not objects have to implement.
+pub trait Light: Sync + Send {
+    /// Light that arrives to `point` from the light, without checking if
+    /// something blocks it. Returns [None] if the light does not reach it.
+    fn illuminate(&self, point: &Vec3) -> Option<Illumination>;
+
+    /// Emits a random ray of light (like a photon). Returns the ray and the
+    /// power it carries divided by the probability density of emitting it.
+    /// `bounds` is the bounding box of the scene, for lights far away.
+    fn emit(&self, time: f64, bounds: &AABB) -> Option<(Ray, Vec3)>;
+}
+
+/// Returns a random unit vector inside a cone around `axis` with the given
+/// cosine of its half angle, and the probability density (with respect to
+/// solid angle) of choosing it.
+fn random_in_cone(axis: &Vec3, cos_max: f64) -> (Vec3, f64) {
+    let cos_theta = 1.0 - rand::random::<f64>() * (1.0 - cos_max);
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = std::f64::consts::TAU * rand::random::<f64>();
+    let local = Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+    let pdf = 1.0 / (std::f64::consts::TAU * (1.0 - cos_max));
+    (ONB::build_from_w(axis).local_vec(&local), pdf)
+}
+
+mod point;
+pub use point::*;
+mod spot;
+pub use spot::*;
+mod directional;
+pub use directional::*;
+mod ies;
+pub use ies::*;
+mod environment;
+pub use environment::*;
@@ -0,0 +1,70 @@
+use super::*;
+use std::f64::consts::PI;
+
+/// Light that shines from a point in every direction
+///
+/// Like a small bulb. The light it gives falls off with the square of the
+/// distance, as any real light does.
+#[derive(Clone)]
+pub struct PointLight {
+    pub position: Vec3,
+    /// Color of the light
+    pub color: Vec3,
+    /// Multiplies the color. It is the radiant intensity (watts per
+    /// steradian) of a white light.
+    pub intensity: f64,
+}
+
+impl PointLight {
+    /// Creates a new [PointLight] of the given color
+    pub fn new(position: Vec3, color: Vec3) -> Self {
+        Self {
+            position,
+            color,
+            intensity: 1.0,
+        }
+    }
+
+    /// Sets the intensity so that the light emits `watts` of power in total
+    pub fn with_power(self, watts: f64) -> Self {
+        let intensity = watts / (4.0 * PI * self.color.luminance());
+        Self { intensity, ..self }
+    }
+}
+
+impl Light for PointLight {
+    fn illuminate(&self, point: &Vec3) -> Option<Illumination> {
+        let to_light = self.position - *point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        Some(Illumination {
+            direction: to_light.unit_vector(),
+            distance: distance_squared.sqrt(),
+            radiance: self.color * self.intensity / distance_squared,
+        })
+    }
+
+    fn emit(&self, time: f64, _bounds: &AABB) -> Option<(Ray, Vec3)> {
+        let direction = Vec3::random_unit_vector();
+        let power = self.color * (self.intensity * 4.0 * PI);
+        Some((Ray::new(self.position, direction, time), power))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_square_falloff() {
+        let light = PointLight::new(Vec3::new(0.0, 2.0, 0.0), Vec3::one()).with_power(4.0 * PI);
+        let near = light.illuminate(&Vec3::new(0.0, 1.0, 0.0)).unwrap();
+        let far = light.illuminate(&Vec3::new(0.0, -2.0, 0.0)).unwrap();
+        assert_eq!(near.direction, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(near.distance, 1.0);
+        assert!((near.radiance - Vec3::one()).length() < 1e-12);
+        assert!((far.radiance - Vec3::splat(1.0 / 16.0)).length() < 1e-12);
+    }
+}
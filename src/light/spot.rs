@@ -0,0 +1,158 @@
+use super::*;
+use std::f64::consts::TAU;
+
+/// Light that shines from a point inside a cone
+///
+/// Like a stage light or a flashlight. The edge of the cone can be soft,
+/// and the light inside it can follow an [IesProfile] measured from a
+/// real luminaire. Falls off with the square of the distance.
+#[derive(Clone)]
+pub struct SpotLight {
+    pub position: Vec3,
+    /// Direction of the axis of the cone
+    pub direction: Vec3,
+    /// Color of the light
+    pub color: Vec3,
+    /// Multiplies the color. It is the radiant intensity (watts per
+    /// steradian) of a white light along the axis.
+    pub intensity: f64,
+    /// Angle in degrees between the axis and the edge of the cone
+    pub angle: f64,
+    /// Fraction [`0.0`, `1.0`] of the cone, from its edge, where the light
+    /// fades out. `0.0` is a hard edge.
+    pub blend: f64,
+    /// Intensity in each direction, with the nadir of the profile along the
+    /// axis of the cone. The horizontal angle `0` is an arbitrary direction
+    /// across the axis. Use an angle of `180` to only follow the profile.
+    pub profile: Option<IesProfile>,
+}
+
+impl SpotLight {
+    /// Creates a new [SpotLight] of the given color with a cone of `angle`
+    /// degrees and a soft edge
+    pub fn new(position: Vec3, direction: Vec3, color: Vec3, angle: f64) -> Self {
+        Self {
+            position,
+            direction,
+            color,
+            intensity: 1.0,
+            angle,
+            blend: 0.15,
+            profile: None,
+        }
+    }
+
+    /// Sets the intensity so that the light emits `watts` of power in total.
+    /// The [profile](SpotLight::profile) is not taken into account, so a
+    /// light with one emits the power of the same cone without it.
+    pub fn with_power(self, watts: f64) -> Self {
+        let cos_outer = self.angle.min(180.0).to_radians().cos();
+        let cos_inner = (self.angle * (1.0 - self.blend))
+            .min(180.0)
+            .to_radians()
+            .cos();
+        // The smooth edge emits half of the light of its band of the cone
+        let solid_angle = TAU * (1.0 - 0.5 * (cos_inner + cos_outer));
+        let intensity = watts / (solid_angle * self.color.luminance());
+        Self { intensity, ..self }
+    }
+
+    /// Fraction of the intensity emitted in the unit `direction`
+    fn falloff(&self, direction: &Vec3) -> f64 {
+        let onb = ONB::build_from_w(&self.direction);
+        let cos_theta = direction.dot(&onb.w);
+        let cos_outer = self.angle.to_radians().cos();
+        let cos_inner = (self.angle * (1.0 - self.blend)).to_radians().cos();
+        let edge = if cos_theta <= cos_outer {
+            0.0
+        } else if cos_theta >= cos_inner {
+            1.0
+        } else {
+            let t = (cos_theta - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        };
+
+        match &self.profile {
+            Some(profile) if edge > 0.0 => {
+                let vertical = cos_theta.clamp(-1.0, 1.0).acos().to_degrees();
+                let horizontal = direction
+                    .dot(&onb.v)
+                    .atan2(direction.dot(&onb.u))
+                    .to_degrees();
+                edge * profile.intensity(vertical, horizontal)
+            }
+            _ => edge,
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn illuminate(&self, point: &Vec3) -> Option<Illumination> {
+        let to_light = self.position - *point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        let direction = to_light.unit_vector();
+        let falloff = self.falloff(&-direction);
+        if falloff <= 0.0 {
+            return None;
+        }
+        Some(Illumination {
+            direction,
+            distance: distance_squared.sqrt(),
+            radiance: self.color * (self.intensity * falloff / distance_squared),
+        })
+    }
+
+    fn emit(&self, time: f64, _bounds: &AABB) -> Option<(Ray, Vec3)> {
+        let cos_outer = self.angle.min(180.0).to_radians().cos();
+        let (direction, pdf) = random_in_cone(&self.direction, cos_outer);
+        let falloff = self.falloff(&direction);
+        if falloff <= 0.0 {
+            return None;
+        }
+        let power = self.color * (self.intensity * falloff / pdf);
+        Some((Ray::new(self.position, direction, time), power))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn power_over_the_cone() {
+        let light = SpotLight {
+            blend: 0.3,
+            ..SpotLight::new(
+                Vec3::zero(),
+                Vec3::new(0.0, -1.0, 0.0),
+                Vec3::new(1.0, 0.5, 0.25),
+                40.0,
+            )
+        }
+        .with_power(100.0);
+
+        // Integrates the intensity over the sphere of directions
+        let (n_theta, n_phi) = (2000, 8);
+        let d_theta = PI / n_theta as f64;
+        let d_phi = TAU / n_phi as f64;
+        let mut falloff = 0.0;
+        for i in 0..n_theta {
+            let theta = (i as f64 + 0.5) * d_theta;
+            for j in 0..n_phi {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let direction = Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                falloff += light.falloff(&direction) * theta.sin() * d_theta * d_phi;
+            }
+        }
+        let power = light.intensity * light.color.luminance() * falloff;
+        assert!((power - 100.0).abs() < 0.1, "{}", power);
+    }
+}
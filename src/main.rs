@@ -2,11 +2,13 @@ use ray_tracing::bvh;
 use ray_tracing::camera::Camera;
 use ray_tracing::hittable::Hittable;
 use ray_tracing::image_helper::Image;
+use ray_tracing::integrator;
+use ray_tracing::light::{EnvironmentLight, Light};
 use ray_tracing::render::*;
 use ray_tracing::scenes;
-use vec3::Vec3;
 
 use std::fs::File;
+use std::io::Write;
 
 enum Output {
     File(String, image::ImageFormat),
@@ -22,21 +24,39 @@ pub struct Config {
     print_debug: bool,
     output: Output,
     scene_name: String,
+    integrator_name: String,
+    debug_view: Option<String>,
     avoid_bvh: bool,
     force_plain_ppm: bool,
+    environment: Option<String>,
+    environment_rotation: Option<f64>,
+    environment_intensity: Option<f64>,
 }
 
 impl Config {
     pub fn build_render_config(
         &self,
-        background_color: Vec3,
+        environment: EnvironmentLight,
         world: Box<dyn Hittable>,
+        lights: Box<dyn Hittable>,
+        delta_lights: Vec<Box<dyn Light>>,
         camera: Camera,
     ) -> RenderConfig {
         RenderConfig {
             world,
+            lights,
+            delta_lights,
             camera,
-            background_color,
+            integrator: if let Some(debug_view) = &self.debug_view {
+                Box::new(
+                    integrator::DebugView::from_name(debug_view)
+                        .expect("Cannot build unknown debug view"),
+                )
+            } else {
+                integrator::get_integrator_from_name(&self.integrator_name)
+                    .expect("Cannot build unknown integrator")
+            },
+            environment,
             image_width: (self.image_height as f64 * self.aspect_ratio).floor() as usize,
             image_height: self.image_height,
             samples_per_pixel: self.samples_per_pixel,
@@ -102,9 +122,37 @@ pub fn get_config() -> Config {
         .long("debug")
         .short("d")
         .help("Increases the logging level."))
+    .arg(Arg::with_name("integrator")
+        .long("integrator")
+        .help("Light transport algorithm used to render.")
+        .value_name("INTEGRATOR")
+        .takes_value(true)
+        .possible_values(&integrator::get_integrators()))
+    .arg(Arg::with_name("debug_view")
+        .long("debug-view")
+        .help("Shows surface data of the first hit instead of rendering the light.")
+        .value_name("MODE")
+        .takes_value(true)
+        .possible_values(&integrator::get_debug_views())
+        .conflicts_with("integrator"))
     .arg(Arg::with_name("avoid_bvh")
         .long("avoid_bvh")
         .help("Avoid to build a BVH with all the objects. May be faster to render a simple scene."))
+    .arg(Arg::with_name("environment")
+        .long("environment")
+        .help("Equirectangular image (like an HDR panorama) that surrounds the scene and lights it, replacing its background.")
+        .value_name("IMAGE")
+        .takes_value(true))
+    .arg(Arg::with_name("environment_rotation")
+        .long("environment-rotation")
+        .help("Rotation in degrees of the environment around the vertical axis.")
+        .value_name("DEGREES")
+        .takes_value(true))
+    .arg(Arg::with_name("environment_intensity")
+        .long("environment-intensity")
+        .help("Multiplies the light of the environment.")
+        .value_name("INTENSITY")
+        .takes_value(true))
     .arg(Arg::with_name("scene")
         .takes_value(true)
         .required(true)
@@ -165,9 +213,21 @@ pub fn get_config() -> Config {
 
     let scene_name = args.value_of("scene").unwrap_or("cornell_box").to_string();
 
+    let integrator_name = args.value_of("integrator").unwrap_or("path").to_string();
+
+    let debug_view = args.value_of("debug_view").map(str::to_string);
+
     let avoid_bvh = args.is_present("avoid_bvh");
     let force_plain_ppm = args.is_present("plain_ppm");
 
+    let environment = args.value_of("environment").map(str::to_string);
+    let environment_rotation = args
+        .value_of("environment_rotation")
+        .map(|r| r.parse().expect("Invalid environment rotation"));
+    let environment_intensity = args
+        .value_of("environment_intensity")
+        .map(|i| i.parse().expect("Invalid environment intensity"));
+
     Config {
         image_height,
         aspect_ratio,
@@ -177,8 +237,13 @@ pub fn get_config() -> Config {
         print_debug,
         output,
         scene_name,
+        integrator_name,
+        debug_view,
         avoid_bvh,
         force_plain_ppm,
+        environment,
+        environment_rotation,
+        environment_intensity,
     }
 }
 
@@ -202,26 +267,50 @@ fn main() -> Result<(), image::ImageError> {
     // Camera
     let cam = Camera::new(&scene.camera_config, config.aspect_ratio);
 
+    // Environment
+    let mut environment = match &config.environment {
+        Some(filename) => {
+            EnvironmentLight::new(filename).expect("Cannot load the environment image")
+        }
+        None => scene.environment,
+    };
+    if let Some(rotation) = config.environment_rotation {
+        environment = environment.with_rotation(rotation);
+    }
+    if let Some(intensity) = config.environment_intensity {
+        environment = environment.with_intensity(intensity);
+    }
+
     // Render
     if config.print_debug {
         eprintln!("Scene: {}", config.scene_name);
+        if let Some(debug_view) = &config.debug_view {
+            eprintln!("Debug view: {}", debug_view);
+        } else {
+            eprintln!("Integrator: {}", config.integrator_name);
+        }
         eprintln!("Aspect ratio: {}", config.aspect_ratio);
         eprintln!("SPP: {}", config.samples_per_pixel);
         eprintln!("Max bounces: {}", config.max_bounces);
         eprintln!("Global BVH: {}", !config.avoid_bvh);
     }
 
-    let (img, elapsed) = render(config.build_render_config(scene.background_color, world, cam));
+    let (img, elapsed) = render(config.build_render_config(
+        environment,
+        world,
+        Box::new(scene.lights),
+        scene.delta_lights,
+        cam,
+    ));
 
     let (render_time, unit) = {
         let mut render_time = elapsed.as_secs_f64();
-        let unit;
-        if render_time > 60.0 {
+        let unit = if render_time > 60.0 {
             render_time /= 60.0;
-            unit = "min"
+            "min"
         } else {
-            unit = "sec"
-        }
+            "sec"
+        };
         (render_time, unit)
     };
     eprintln!("\nDone! Rendered in {:.3} {}", render_time, unit);
@@ -267,7 +356,10 @@ fn main() -> Result<(), image::ImageError> {
                 img.write_to(&mut file, format)?;
             }
             Output::Stdout(format) => {
-                img.write_to(&mut std::io::stdout(), format)?;
+                // Some encoders need to seek, which the standard output can't
+                let mut buffer = std::io::Cursor::new(Vec::new());
+                img.write_to(&mut buffer, format)?;
+                std::io::stdout().write_all(buffer.get_ref())?;
             }
         };
     }
@@ -0,0 +1,129 @@
+use super::*;
+
+/// Step in the surface coords used to find the slope of the height
+const BUMP_DELTA: f64 = 0.0005;
+
+/// Bump map
+///
+/// Wraps another material, shading it as if the surface was displaced
+/// along its normal by the heights of a texture (its first channel). Any
+/// texture works, like a [NoiseTexture](crate::texture::NoiseTexture) for
+/// a rough stone or an image for engravings.
+#[derive(Clone)]
+pub struct BumpMap<M: Material, T: Texture> {
+    /// Material shaded with the new normals
+    pub material: M,
+    /// Texture with the height of the surface
+    pub height: T,
+    /// Multiplies the heights of the texture
+    pub scale: f64,
+}
+
+impl<M: Material, T: Texture> BumpMap<M, T> {
+    /// Displacement of the surface at coords (`u`, `v`) and point `p`,
+    /// filtered over a square of side `width` like [Texture::filtered]
+    fn displacement(&self, u: f64, v: f64, p: &Vec3, width: f64) -> f64 {
+        self.scale * self.height.filtered(u, v, p, width).x()
+    }
+
+    /// Returns the hit shaded with the normal of the displaced surface. Rays
+    /// that would arrive from below the new normal keep the original hit.
+    fn shade<'a>(&self, r_in: &Ray, hit: &HitRecord<'a>) -> HitRecord<'a> {
+        let width = hit.uv_footprint();
+        let height = self.displacement(hit.u, hit.v, &hit.point, width);
+        let height_u = self.displacement(
+            hit.u + BUMP_DELTA,
+            hit.v,
+            &(hit.point + BUMP_DELTA * hit.dpdu),
+            width,
+        );
+        let height_v = self.displacement(
+            hit.u,
+            hit.v + BUMP_DELTA,
+            &(hit.point + BUMP_DELTA * hit.dpdv),
+            width,
+        );
+
+        let normal = hit.normal;
+        let dpdu = hit.dpdu + (height_u - height) / BUMP_DELTA * normal;
+        let dpdv = hit.dpdv + (height_v - height) / BUMP_DELTA * normal;
+        let mut shading_normal = dpdu.cross(&dpdv);
+        // The tangents may be oriented against the normal
+        if hit.dpdu.cross(&hit.dpdv).dot(&normal) < 0.0 {
+            shading_normal = -shading_normal;
+        }
+
+        if shading_normal.near_zero() || shading_normal.dot(&r_in.direction) >= 0.0 {
+            return hit.clone();
+        }
+        hit.with_shading_normal(shading_normal)
+    }
+}
+
+impl<M: Material, T: Texture> Material for BumpMap<M, T> {
+    fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.material.scatter(r_in, &self.shade(r_in, hit))
+    }
+
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        self.material.emitted(hit)
+    }
+
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        self.material.eval(r_in, &self.shade(r_in, hit), direction)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        self.material
+            .scattering_pdf(r_in, &self.shade(r_in, hit), direction)
+    }
+
+    fn is_specular(&self, hit: &HitRecord) -> bool {
+        self.material.is_specular(hit)
+    }
+
+    fn is_volumetric(&self) -> bool {
+        self.material.is_volumetric()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+    use crate::texture::SolidColor;
+
+    #[test]
+    fn constant_height_keeps_the_normal() {
+        let material = BumpMap {
+            material: Lambertian::from_color(Vec3::one()),
+            height: SolidColor::gray(0.7),
+            scale: 2.0,
+        };
+        let (r, hit) = hit_from(Vec3::new(1.0, 2.0, 3.0), &material);
+        let shaded = material.shade(&r, &hit);
+        assert!((shaded.normal - hit.normal).length() < 1e-9);
+    }
+
+    /// Height that grows as `u`
+    struct Ramp;
+
+    impl Texture for Ramp {
+        fn value(&self, u: f64, _v: f64, _p: &Vec3) -> Vec3 {
+            Vec3::splat(u)
+        }
+    }
+
+    #[test]
+    fn slope_tilts_the_normal() {
+        let material = BumpMap {
+            material: Lambertian::from_color(Vec3::one()),
+            height: Ramp,
+            scale: 1.0,
+        };
+        let (r, hit) = hit_from(Vec3::new(0.0, 0.0, 1.0), &material);
+        let shaded = material.shade(&r, &hit);
+        let expected = Vec3::new(-1.0, 0.0, 1.0).unit_vector();
+        assert!((shaded.normal - expected).length() < 1e-6);
+    }
+}
@@ -0,0 +1,202 @@
+use super::*;
+
+/// Lowest roughness of the coat, so it can be evaluated in any direction
+const MIN_ROUGHNESS: f64 = 0.05;
+
+/// Coated material
+///
+/// Puts a thin dielectric layer (varnish, clearcoat) over any other
+/// material, like plastic, lacquered wood or car paint. The coat reflects
+/// part of the light with a glossy highlight and the rest reaches the
+/// `base`, losing some of it by absorption when crossing the coat in and out.
+///
+/// The coat is only seen from outside the object; rays coming from the
+/// inside go straight to the `base`.
+#[derive(Clone)]
+pub struct Coated<M: Material> {
+    /// Material under the coat
+    pub base: M,
+    /// Refraction index of the coat
+    pub index_refraction: f64,
+    /// Roughness of the surface of the coat [`0.0`, `1.0`]
+    pub roughness: f64,
+    /// Thickness of the coat
+    pub thickness: f64,
+    /// Absorption coefficient of the coat for each color channel. Zero does
+    /// not absorb.
+    pub absorption: Vec3,
+}
+
+impl<M: Material> Coated<M> {
+    /// Creates a clear, smooth coat with refraction index `1.5` over `base`
+    pub fn new(base: M) -> Self {
+        Coated {
+            base,
+            index_refraction: 1.5,
+            roughness: 0.0,
+            thickness: 0.0,
+            absorption: Vec3::zero(),
+        }
+    }
+
+    fn distribution(&self) -> GGX {
+        GGX::isotropic(self.roughness.max(MIN_ROUGHNESS))
+    }
+
+    /// Light that crosses the coat in direction `w` (local space) and is
+    /// not absorbed
+    fn transmittance(&self, w: &Vec3) -> Vec3 {
+        let distance = self.thickness / w.z().abs().max(1e-4);
+        self.absorption.map(|a| (-a * distance).exp())
+    }
+
+    /// Light that reaches the base from `wo` and leaves in `wi`
+    fn base_weight(&self, wo: &Vec3, wi: &Vec3) -> Vec3 {
+        let entering =
+            (1.0 - fresnel_dielectric(wo.z(), self.index_refraction)) * self.transmittance(wo);
+        if wi.z() > 0.0 {
+            let leaving =
+                (1.0 - fresnel_dielectric(wi.z(), self.index_refraction)) * self.transmittance(wi);
+            entering * leaving
+        } else {
+            entering
+        }
+    }
+
+    /// Probability of sampling the reflection of the coat instead of the base
+    fn coat_probability(&self, wo: &Vec3) -> f64 {
+        fresnel_dielectric(wo.z(), self.index_refraction).clamp_(0.2, 0.8)
+    }
+}
+
+impl<M: Material> Material for Coated<M> {
+    fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
+        if !hit.front_face {
+            return self.base.scatter(r_in, hit);
+        }
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+        let coat_probability = self.coat_probability(&wo);
+
+        if coat_probability > rand::random() {
+            let distribution = self.distribution();
+            let wh = distribution.sample_wh(&wo);
+            let wi = reflect(&wo.neg(), &wh);
+            if wi.z() <= 0.0 {
+                return None;
+            }
+            let fresnel = fresnel_dielectric(wo.dot(&wh), self.index_refraction);
+            let g = distribution.g(&wo, &wi) / distribution.g1(&wo);
+            let scattered = Ray::new(hit.point, onb.local_vec(&wi), r_in.time);
+            Some((Vec3::splat(fresnel * g / coat_probability), scattered))
+        } else {
+            let (attenuation, scattered) = self.base.scatter(r_in, hit)?;
+            let wi = onb.to_local(&scattered.direction.unit_vector());
+            let weight = self.base_weight(&wo, &wi) / (1.0 - coat_probability);
+            Some((attenuation * weight, scattered))
+        }
+    }
+
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        self.base.emitted(hit)
+    }
+
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        if !hit.front_face {
+            return self.base.eval(r_in, hit, direction);
+        }
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        let wi = onb.to_local(&direction.unit_vector());
+        if wo.z() <= 0.0 {
+            return Vec3::zero();
+        }
+
+        let base = self.base.eval(r_in, hit, direction) * self.base_weight(&wo, &wi);
+        if wi.z() <= 0.0 {
+            return base;
+        }
+        let distribution = self.distribution();
+        let wh = (wo + wi).unit_vector();
+        let fresnel = fresnel_dielectric(wo.dot(&wh), self.index_refraction);
+        let coat = fresnel * distribution.d(&wh) * distribution.g(&wo, &wi) / (4.0 * wo.z());
+        base + Vec3::splat(coat)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        if !hit.front_face {
+            return self.base.scattering_pdf(r_in, hit, direction);
+        }
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        let wi = onb.to_local(&direction.unit_vector());
+        if wo.z() <= 0.0 {
+            return 0.0;
+        }
+
+        let coat_probability = self.coat_probability(&wo);
+        let base = self.base.scattering_pdf(r_in, hit, direction);
+        let coat = if wi.z() > 0.0 {
+            let wh = (wo + wi).unit_vector();
+            self.distribution().pdf(&wo, &wh) / (4.0 * wo.dot(&wh))
+        } else {
+            0.0
+        };
+        coat_probability * coat + (1.0 - coat_probability) * base
+    }
+
+    /// Only a specular base makes the whole material specular, the coat is
+    /// never perfectly smooth
+    fn is_specular(&self, hit: &HitRecord) -> bool {
+        self.base.is_specular(hit)
+    }
+
+    fn is_volumetric(&self) -> bool {
+        self.base.is_volumetric()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+
+    #[test]
+    fn coat_and_base_weights() {
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let clear = Coated::new(Lambertian::from_color(Vec3::one()));
+        // 4% is reflected by the coat when entering and when leaving
+        let expected = (1.0 - 0.04f64).powi(2);
+        assert!((clear.base_weight(&normal, &normal) - Vec3::splat(expected)).length() < 1e-9);
+
+        // Absorbed once going in and once going out
+        let tinted = Coated {
+            thickness: 0.5,
+            absorption: Vec3::new(0.0, 1.0, 2.0),
+            ..clear.clone()
+        };
+        let absorbed = Vec3::new(1.0, (-1.0f64).exp(), (-2.0f64).exp());
+        let weight = tinted.base_weight(&normal, &normal);
+        assert!((weight - expected * absorbed).length() < 1e-9);
+
+        // Over a black base only the coat reflects
+        for roughness in [0.1, 0.5] {
+            let black = Coated {
+                roughness,
+                ..Coated::new(Lambertian::from_color(Vec3::zero()))
+            };
+            let white = Coated {
+                roughness,
+                ..clear.clone()
+            };
+            let coat = albedo(&black, normal, 20000).x();
+            assert!((coat - 0.04).abs() < 0.01, "{}", coat);
+            // The rest goes to the base, minus what the coat keeps inside
+            let total = albedo(&white, normal, 20000).x();
+            assert!(total > coat + 0.8 && total <= 1.01, "{}", total);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use super::*;
+
+/// Rough metallic material
+///
+/// Physically based alternative to [Metal]. The surface is made of
+/// microfacets following a [GGX] distribution and the light they reflect
+/// is given by the Fresnel equations of a conductor with complex refraction
+/// index `eta + i k`.
+#[derive(Clone)]
+pub struct Conductor {
+    /// Real part of the refraction index for each color channel
+    pub eta: Vec3,
+    /// Imaginary part (absorption) of the refraction index for each color channel
+    pub k: Vec3,
+    /// Distribution of the microfacets. The anisotropic axes follow the
+    /// tangent and bitangent of the hit.
+    pub distribution: GGX,
+    /// Film over the surface that makes reflections iridescent
+    pub thin_film: Option<ThinFilm>,
+}
+
+impl Conductor {
+    /// Creates a conductor with the given complex refraction index and
+    /// microfacet distribution
+    pub fn new(eta: Vec3, k: Vec3, distribution: GGX) -> Self {
+        Conductor {
+            eta,
+            k,
+            distribution,
+            thin_film: None,
+        }
+    }
+
+    /// Gold
+    pub fn gold(distribution: GGX) -> Self {
+        Self::new(
+            Vec3::new(0.143119, 0.374957, 1.44248),
+            Vec3::new(3.98316, 2.38572, 1.60322),
+            distribution,
+        )
+    }
+
+    /// Copper
+    pub fn copper(distribution: GGX) -> Self {
+        Self::new(
+            Vec3::new(0.200438, 0.924033, 1.10221),
+            Vec3::new(3.91295, 2.45285, 2.14219),
+            distribution,
+        )
+    }
+
+    /// Aluminium
+    pub fn aluminium(distribution: GGX) -> Self {
+        Self::new(
+            Vec3::new(1.65746, 0.880369, 0.521229),
+            Vec3::new(9.22387, 6.26952, 4.837),
+            distribution,
+        )
+    }
+
+    /// Silver
+    pub fn silver(distribution: GGX) -> Self {
+        Self::new(
+            Vec3::new(0.155265, 0.116723, 0.138342),
+            Vec3::new(4.82835, 3.12225, 2.14696),
+            distribution,
+        )
+    }
+}
+
+impl Conductor {
+    /// Returns the reflectance for light arriving with angle cosine
+    /// `cos_theta` (through the film if there is one)
+    fn fresnel(&self, r_in: &Ray, hit: &HitRecord, cos_theta: f64) -> Vec3 {
+        match &self.thin_film {
+            Some(film) => film.reflectance(cos_theta, &self.eta, &self.k, r_in.wavelength, hit),
+            None => fresnel_conductor(cos_theta, &self.eta, &self.k),
+        }
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let (attenuation, wi) = if self.distribution.is_smooth() {
+            let wi = Vec3::new(-wo.x(), -wo.y(), wo.z());
+            (self.fresnel(r_in, hit, wo.z()), wi)
+        } else {
+            let wh = self.distribution.sample_wh(&wo);
+            let wi = reflect(&wo.neg(), &wh);
+            if wi.z() <= 0.0 {
+                return None;
+            }
+            // Sampling visible normals leaves only the shadowing term
+            let fresnel = self.fresnel(r_in, hit, wo.dot(&wh));
+            let g = self.distribution.g(&wo, &wi) / self.distribution.g1(&wo);
+            (fresnel * g, wi)
+        };
+
+        let scattered = Ray::new(hit.point, onb.local_vec(&wi), r_in.time);
+        Some((attenuation, scattered))
+    }
+
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        let wi = onb.to_local(&direction.unit_vector());
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Vec3::zero();
+        }
+        let wh = (wo + wi).unit_vector();
+        let fresnel = self.fresnel(r_in, hit, wo.dot(&wh));
+        fresnel * (self.distribution.d(&wh) * self.distribution.g(&wo, &wi) / (4.0 * wo.z()))
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        let wi = onb.to_local(&direction.unit_vector());
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+        let wh = (wo + wi).unit_vector();
+        self.distribution.pdf(&wo, &wh) / (4.0 * wo.dot(&wh))
+    }
+
+    fn is_specular(&self, _hit: &HitRecord) -> bool {
+        self.distribution.is_smooth()
+    }
+}
@@ -1,47 +1,438 @@
 use super::*;
+use crate::spectrum::LAMBDA_D;
+use std::sync::Arc;
+
+/// How the refraction index of a [Dielectric] changes with the
+/// wavelength of light (dispersion)
+#[derive(Clone)]
+pub enum RefractiveIndex {
+    /// Same refraction index for every wavelength
+    Constant(f64),
+    /// Refraction index read from the first channel of a texture, the
+    /// same for every wavelength
+    Textured(Arc<dyn Texture>),
+    /// Cauchy's equation `n = a + b / λ²`, with `λ` in micrometers
+    Cauchy { a: f64, b: f64 },
+    /// Sellmeier's equation `n² = 1 + Σ bᵢ λ² / (λ² - cᵢ)`, with `λ` in
+    /// micrometers
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl RefractiveIndex {
+    /// Borosilicate crown glass (Schott N-BK7)
+    pub fn bk7() -> Self {
+        RefractiveIndex::Sellmeier {
+            b: [1.03961212, 0.231792344, 1.01046945],
+            c: [0.00600069867, 0.0200179144, 103.560653],
+        }
+    }
+
+    /// Fused silica (quartz glass)
+    pub fn fused_silica() -> Self {
+        RefractiveIndex::Sellmeier {
+            b: [0.6961663, 0.4079426, 0.8974794],
+            c: [0.00467914826, 0.0135120631, 97.9340025],
+        }
+    }
+
+    /// Dense flint glass (Schott SF11), which disperses light strongly
+    pub fn dense_flint() -> Self {
+        RefractiveIndex::Sellmeier {
+            b: [1.73759695, 0.313747346, 1.89878101],
+            c: [0.013188707, 0.0623068142, 155.23629],
+        }
+    }
+
+    /// Returns the refraction index at `wavelength` (in nanometers) and the
+    /// hit. Without a wavelength, the index at the sodium d-line is returned.
+    pub fn at(&self, wavelength: Option<f64>, hit: &HitRecord) -> f64 {
+        let lambda = wavelength.unwrap_or(LAMBDA_D) / 1000.0;
+        let lambda2 = lambda * lambda;
+        match self {
+            RefractiveIndex::Constant(n) => *n,
+            RefractiveIndex::Textured(texture) => texture.value_at(hit).x(),
+            RefractiveIndex::Cauchy { a, b } => a + b / lambda2,
+            RefractiveIndex::Sellmeier { b, c } => (1.0
+                + b.iter()
+                    .zip(c.iter())
+                    .map(|(b, c)| b * lambda2 / (lambda2 - c))
+                    .sum::<f64>())
+            .sqrt(),
+        }
+    }
+}
+
+impl From<f64> for RefractiveIndex {
+    fn from(n: f64) -> Self {
+        RefractiveIndex::Constant(n)
+    }
+}
 
 /// Dielectric material
 ///
-/// Glass
+/// Glass. With a [roughness](Dielectric::roughness) the interface is made
+/// of microfacets that blur reflections and refractions (frosted glass).
 #[derive(Clone)]
-pub struct Dielectric {
+pub struct Dielectric<R: Texture, T: Texture> {
     /// Refraction index of the material. Void is `1.0`.
-    pub index_refraction: f64,
+    pub index_refraction: RefractiveIndex,
+    /// Roughness [`0.0`, `1.0`] of the interface. The first channel is the
+    /// roughness along the tangent of the hit and the second one along
+    /// the bitangent.
+    pub roughness: R,
+    /// Color of the light after travelling [tint_distance](Dielectric::tint_distance)
+    /// inside the material (Beer–Lambert absorption). White does not absorb.
+    /// It is read where the light leaves the material.
+    pub tint: T,
+    /// Distance at which light inside the material has the [tint](Dielectric::tint) color
+    pub tint_distance: f64,
+    /// Film over the surface that makes reflections iridescent
+    pub thin_film: Option<ThinFilm>,
 }
 
-/// Calculates the reflectance based on the cosine of the angle
-/// of hit and the refraction index
-fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
-    // Schlick's approximation
-    let r0 = (1.0 - ref_idx) / (1.0 + ref_idx);
-    let r0 = r0 * r0;
-    r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+impl Dielectric<SolidColor, SolidColor> {
+    /// Creates a smooth and clear Dielectric with the same refraction index
+    /// for every wavelength
+    pub fn new(index_refraction: f64) -> Self {
+        Dielectric {
+            index_refraction: RefractiveIndex::Constant(index_refraction),
+            roughness: SolidColor::gray(0.0),
+            tint: SolidColor::gray(1.0),
+            tint_distance: 1.0,
+            thin_film: None,
+        }
+    }
 }
 
-impl Material for Dielectric {
+impl<R: Texture, T: Texture> Dielectric<R, T> {
+    /// Returns the microfacet distribution at the hit
+    fn distribution(&self, hit: &HitRecord) -> GGX {
+        let roughness = self.roughness.value_at(hit);
+        GGX::anisotropic(roughness.x(), roughness.y())
+    }
+
+    /// Returns the light that is not absorbed by the material along `r_in`
+    /// until `hit`, if the ray travelled inside
+    fn transmittance(&self, r_in: &Ray, hit: &HitRecord) -> Vec3 {
+        if hit.front_face {
+            return Vec3::one();
+        }
+        let tint = self.tint.value_at(hit);
+        if tint == Vec3::one() {
+            return tint;
+        }
+        let distance = hit.t * r_in.direction.length() / self.tint_distance;
+        tint.map(|c| (c.clamp_(1e-6, 1.0).ln() * distance).exp())
+    }
+
+    /// Returns the ratio between the refraction index at the other side of
+    /// the interface and the one at the side of the ray
+    fn eta(&self, r_in: &Ray, hit: &HitRecord) -> f64 {
+        let index_refraction = self.index_refraction.at(r_in.wavelength, hit);
+        if hit.front_face {
+            index_refraction
+        } else {
+            1.0 / index_refraction
+        }
+    }
+
+    /// Returns the reflectance of the interface for light arriving with
+    /// angle cosine `cos_theta` (through the film if there is one). `eta` is
+    /// the one given by [eta](Dielectric::eta).
+    fn fresnel(&self, r_in: &Ray, hit: &HitRecord, cos_theta: f64, eta: f64) -> Vec3 {
+        let film = match &self.thin_film {
+            Some(film) => film,
+            None => return Vec3::splat(fresnel_dielectric(cos_theta, eta)),
+        };
+        if hit.front_face {
+            return film.reflectance(
+                cos_theta,
+                &Vec3::splat(eta),
+                &Vec3::zero(),
+                r_in.wavelength,
+                hit,
+            );
+        }
+        // The film is outside, and it reflects the same light coming from
+        // inside as from the direction it refracts to outside
+        let sin2_outside = (1.0 - cos_theta * cos_theta) / (eta * eta);
+        if sin2_outside >= 1.0 {
+            return Vec3::one();
+        }
+        film.reflectance(
+            (1.0 - sin2_outside).sqrt(),
+            &Vec3::splat(1.0 / eta),
+            &Vec3::zero(),
+            r_in.wavelength,
+            hit,
+        )
+    }
+}
+
+/// Average of the color channels
+fn average(color: &Vec3) -> f64 {
+    (color.x() + color.y() + color.z()) / 3.0
+}
+
+/// Returns the half vector of a refraction from `wo` to `wi` across an
+/// interface with relative refraction index `eta`, oriented like the normal
+fn refraction_half_vector(wo: &Vec3, wi: &Vec3, eta: f64) -> Vec3 {
+    let wh = (*wo + eta * *wi).unit_vector();
+    if wh.z() < 0.0 {
+        wh.neg()
+    } else {
+        wh
+    }
+}
+
+impl<R: Texture, T: Texture> Material for Dielectric<R, T> {
     fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
-        let attenuation = Vec3::one();
-        let refraction_ratio = if hit.front_face {
-            1.0 / self.index_refraction
+        let attenuation = self.transmittance(r_in, hit);
+        let eta = self.eta(r_in, hit);
+        let distribution = self.distribution(hit);
+
+        if distribution.is_smooth() {
+            let refraction_ratio = 1.0 / eta;
+            let unit_direction = r_in.direction.unit_vector();
+            let cos_theta = unit_direction.neg().dot(&hit.normal).min(1.0);
+            let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+            // Cannot refract, no solution for Snell equation => reflect
+            let cannot_refract = refraction_ratio * sin_theta > 1.0;
+            if cannot_refract {
+                let direction = reflect(&unit_direction, &hit.normal);
+                return Some((attenuation, Ray::new(hit.point, direction, r_in.time)));
+            }
+            // Some rays will be reflected
+            let reflectance = self.fresnel(r_in, hit, cos_theta, eta);
+
+            // Reflection is chosen with the average reflectance, and the
+            // color left is carried by the weight
+            let probability = average(&reflectance);
+            let (direction, weight) = if probability > rand::random() {
+                let direction = reflect(&unit_direction, &hit.normal);
+                (direction, reflectance / probability)
+            } else {
+                let direction = refract(&unit_direction, &hit.normal, refraction_ratio);
+                (direction, (Vec3::one() - reflectance) / (1.0 - probability))
+            };
+
+            let scattered = Ray::new(hit.point, direction, r_in.time);
+            return Some((attenuation * weight, scattered));
+        }
+
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+        let wh = distribution.sample_wh(&wo);
+        let cos_o = wo.dot(&wh);
+        let fresnel = self.fresnel(r_in, hit, cos_o, eta);
+        let probability = average(&fresnel);
+
+        // Reflection and refraction are chosen proportionally to the
+        // Fresnel term, so it cancels out of the weight (but its color)
+        let (wi, weight) = if probability > rand::random() {
+            let wi = reflect(&wo.neg(), &wh);
+            if wi.z() <= 0.0 {
+                return None;
+            }
+            (wi, fresnel / probability)
         } else {
-            self.index_refraction
+            let wi = refract(&wo.neg(), &wh, 1.0 / eta);
+            if wi.z() >= 0.0 {
+                return None;
+            }
+            (wi, (Vec3::one() - fresnel) / (1.0 - probability))
         };
+        // Sampling visible normals leaves only the shadowing term
+        let g = distribution.g(&wo, &wi) / distribution.g1(&wo);
+
+        let scattered = Ray::new(hit.point, onb.local_vec(&wi), r_in.time);
+        Some((attenuation * weight * g, scattered))
+    }
 
-        let unit_direction = r_in.direction.unit_vector();
-        let cos_theta = unit_direction.neg().dot(&hit.normal).min(1.0);
-        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
-        // Cannot refract, no solution for Snell equation => reflect
-        let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        // Some rays will be reflected
-        let reflectance = reflectance(cos_theta, refraction_ratio);
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        let wi = onb.to_local(&direction.unit_vector());
+        if wo.z() <= 0.0 || wi.z() == 0.0 {
+            return Vec3::zero();
+        }
+        let eta = self.eta(r_in, hit);
+        let distribution = self.distribution(hit);
 
-        let direction = if cannot_refract || reflectance > rand::random() {
-            reflect(&unit_direction, &hit.normal)
+        let value = if wi.z() > 0.0 {
+            let wh = (wo + wi).unit_vector();
+            let fresnel = self.fresnel(r_in, hit, wo.dot(&wh), eta);
+            fresnel * (distribution.d(&wh) * distribution.g(&wo, &wi) / (4.0 * wo.z()))
         } else {
-            refract(&unit_direction, &hit.normal, refraction_ratio)
+            let wh = refraction_half_vector(&wo, &wi, eta);
+            let cos_o = wo.dot(&wh);
+            let cos_i = wi.dot(&wh);
+            // Both directions have to be at opposite sides of the microfacet
+            if cos_o <= 0.0 || cos_i >= 0.0 {
+                return Vec3::zero();
+            }
+            let fresnel = self.fresnel(r_in, hit, cos_o, eta);
+            let denom = cos_o + eta * cos_i;
+            (Vec3::one() - fresnel)
+                * (distribution.d(&wh)
+                    * distribution.g(&wo, &wi)
+                    * eta
+                    * eta
+                    * (cos_i * cos_o).abs()
+                    / (wo.z() * denom * denom))
         };
+        self.transmittance(r_in, hit) * value
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        let wi = onb.to_local(&direction.unit_vector());
+        if wo.z() <= 0.0 || wi.z() == 0.0 {
+            return 0.0;
+        }
+        let eta = self.eta(r_in, hit);
+        let distribution = self.distribution(hit);
+
+        if wi.z() > 0.0 {
+            let wh = (wo + wi).unit_vector();
+            let fresnel = average(&self.fresnel(r_in, hit, wo.dot(&wh), eta));
+            fresnel * distribution.pdf(&wo, &wh) / (4.0 * wo.dot(&wh))
+        } else {
+            let wh = refraction_half_vector(&wo, &wi, eta);
+            let cos_o = wo.dot(&wh);
+            let cos_i = wi.dot(&wh);
+            if cos_o <= 0.0 || cos_i >= 0.0 {
+                return 0.0;
+            }
+            let fresnel = average(&self.fresnel(r_in, hit, cos_o, eta));
+            let denom = cos_o + eta * cos_i;
+            (1.0 - fresnel) * distribution.pdf(&wo, &wh) * eta * eta * cos_i.abs() / (denom * denom)
+        }
+    }
+
+    fn is_specular(&self, hit: &HitRecord) -> bool {
+        self.distribution(hit).is_smooth()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+    use crate::texture::UvChecker;
+
+    #[test]
+    fn sellmeier_bk7() {
+        let bk7 = RefractiveIndex::bk7();
+        let glass = Dielectric::new(1.5);
+        let (_, hit) = hit_from(Vec3::new(0.0, 0.0, 1.0), &glass);
+        assert!((bk7.at(None, &hit) - 1.5168).abs() < 1e-4);
+        // Blue light bends more than red light
+        assert!(bk7.at(Some(450.0), &hit) > bk7.at(Some(650.0), &hit));
+    }
+
+    #[test]
+    fn smooth_reflects_with_exact_fresnel() {
+        let glass = Dielectric::new(1.5);
+        // Light arriving at 60 degrees from the normal
+        let from = Vec3::new(3.0f64.sqrt(), 0.0, 1.0);
+        let (r, hit) = hit_from(from, &glass);
+        let samples = 20000;
+        let reflected = (0..samples)
+            .filter_map(|_| glass.scatter(&r, &hit))
+            .filter(|(_, scattered)| scattered.direction.z() > 0.0)
+            .count();
+        let expected = fresnel_dielectric(0.5, 1.5);
+        assert!(
+            (reflected as f64 / samples as f64 - expected).abs() < 0.01,
+            "{} reflected, expected {}",
+            reflected as f64 / samples as f64,
+            expected
+        );
+    }
+
+    /// Dielectric with the given index, roughness and tint, without a film
+    fn dielectric<R: Texture, T: Texture>(
+        index_refraction: RefractiveIndex,
+        roughness: R,
+        tint: T,
+    ) -> Dielectric<R, T> {
+        Dielectric {
+            index_refraction,
+            roughness,
+            tint,
+            tint_distance: 1.0,
+            thin_film: None,
+        }
+    }
+
+    /// Texture that is `odd` where `u` > `0.5` and `even` elsewhere
+    fn halves(odd: f64, even: f64) -> UvChecker<SolidColor, SolidColor> {
+        UvChecker::from_colors(Vec3::splat(odd), Vec3::splat(even), (2.0, 1.0))
+    }
+
+    #[test]
+    fn textured_index_refraction() {
+        let glass = dielectric(
+            RefractiveIndex::Textured(Arc::new(halves(1.5, 1.0))),
+            SolidColor::gray(0.0),
+            SolidColor::gray(1.0),
+        );
+        let from = Vec3::new(1.0, 0.0, 1.0);
+        let (r, mut hit) = hit_from(from, &glass);
+        hit.u = 0.25;
+        assert_eq!(glass.index_refraction.at(None, &hit), 1.0);
+        // Without a change of index light goes straight through
+        let (_, scattered) = glass.scatter(&r, &hit).unwrap();
+        assert!((scattered.direction.unit_vector() + from.unit_vector()).length() < 1e-9);
+
+        hit.u = 0.75;
+        assert_eq!(glass.index_refraction.at(None, &hit), 1.5);
+        let (_, scattered) = (0..100)
+            .filter_map(|_| glass.scatter(&r, &hit))
+            .find(|(_, scattered)| scattered.direction.z() < 0.0)
+            .unwrap();
+        // The refracted ray bends towards the normal
+        let sin_t = scattered.direction.unit_vector().x().abs();
+        assert!((sin_t - 0.5f64.sqrt() / 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn textured_roughness() {
+        let glass = dielectric(
+            RefractiveIndex::Constant(1.5),
+            halves(0.5, 0.0),
+            SolidColor::gray(1.0),
+        );
+        let (r, mut hit) = hit_from(Vec3::new(1.0, 0.0, 1.0), &glass);
+        let direction = Vec3::new(-1.0, 0.0, 1.0);
+        hit.u = 0.25;
+        assert!(glass.is_specular(&hit));
+        hit.u = 0.75;
+        assert!(!glass.is_specular(&hit));
+        assert!(glass.eval(&r, &hit, &direction).x() > 0.0);
+        // The hit of the helper is at `u` = `0.5`, in the rough half
+        assert_scatter_matches_eval(&glass, Vec3::new(1.0, 0.0, 1.0));
+    }
 
-        let scattered = Ray::new(hit.point, direction, r_in.time);
-        Some((attenuation, scattered))
+    #[test]
+    fn textured_tint() {
+        let glass = dielectric(
+            RefractiveIndex::Constant(1.5),
+            SolidColor::gray(0.0),
+            halves(0.5, 1.0),
+        );
+        // Light leaving the glass after travelling a distance of `1.0`
+        let (r, mut hit) = hit_from(Vec3::new(0.0, 0.0, -1.0), &glass);
+        hit.u = 0.25;
+        let (attenuation, _) = glass.scatter(&r, &hit).unwrap();
+        assert_eq!(attenuation, Vec3::one());
+        hit.u = 0.75;
+        let (attenuation, _) = glass.scatter(&r, &hit).unwrap();
+        assert!((attenuation - Vec3::splat(0.5)).length() < 1e-9);
     }
 }
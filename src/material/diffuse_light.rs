@@ -1,20 +1,108 @@
 use super::*;
+use crate::spectrum::blackbody;
+use std::f64::consts::PI;
+
+/// Luminous efficacy (lumens per watt) used to convert lumens into watts.
+/// It is the maximum possible, of light at 555nm.
+const LUMENS_PER_WATT: f64 = 683.0;
+
+/// Number of lookups along `u` and `v` used to average a texture
+const AVERAGE_SAMPLES: usize = 64;
+
+/// Returns the average luminance of a texture over the surface coords
+/// in [`0.0`, `1.0`]
+fn average_luminance<T: Texture>(texture: &T) -> f64 {
+    let step = 1.0 / AVERAGE_SAMPLES as f64;
+    let total: f64 = (0..AVERAGE_SAMPLES * AVERAGE_SAMPLES)
+        .map(|i| {
+            let u = ((i % AVERAGE_SAMPLES) as f64 + 0.5) * step;
+            let v = ((i / AVERAGE_SAMPLES) as f64 + 0.5) * step;
+            texture.value(u, v, &Vec3::zero()).luminance()
+        })
+        .sum();
+    total * step * step
+}
+
+/// Faces of a surface that emit light
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightSides {
+    /// Only the face the outward normal of the object points to
+    Front,
+    /// Only the face opposite to the outward normal of the object
+    Back,
+    /// Both faces
+    Both,
+}
+
+impl LightSides {
+    /// Number of faces that emit
+    fn count(&self) -> f64 {
+        match self {
+            LightSides::Both => 2.0,
+            _ => 1.0,
+        }
+    }
+}
 
 /// Diffuse light material
 ///
-/// Does not scatters lay but emits a texture
+/// Does not scatters lay but emits a texture, the same in every direction.
+/// The texture can be anything, like an [ImageTexture](crate::texture::ImageTexture) for a screen.
+///
+/// The power of a light of known area can be given in physical units with
+/// [with_power](DiffuseLight::with_power) or [with_lumens](DiffuseLight::with_lumens).
 #[derive(Clone)]
 pub struct DiffuseLight<T: Texture> {
     /// Texture the material emits
-    emit: T,
+    pub emit: T,
+    /// Multiplies the emitted light
+    pub intensity: f64,
+    /// Faces of the surface that emit
+    pub sides: LightSides,
+}
+
+impl<T: Texture> DiffuseLight<T> {
+    /// Constructs a [DiffuseLight] material that emits the texture from both faces
+    pub fn new(emit: T) -> Self {
+        Self {
+            emit,
+            intensity: 1.0,
+            sides: LightSides::Both,
+        }
+    }
+
+    /// Sets the intensity so a light with surface `area` emits `watts` of
+    /// radiant flux in total, keeping its texture. The texture is averaged
+    /// over the surface coords in [`0.0`, `1.0`], at the origin for the
+    /// textures that depend on the point.
+    pub fn with_power(self, watts: f64, area: f64) -> Self {
+        let luminance = average_luminance(&self.emit);
+        if luminance <= 0.0 || area <= 0.0 {
+            return self;
+        }
+        // A lambertian emitter of radiance L emits L * PI * area per face
+        let intensity = watts / (PI * area * self.sides.count() * luminance);
+        Self { intensity, ..self }
+    }
+
+    /// Sets the intensity so a light with surface `area` emits `lumens` of
+    /// luminous flux in total, keeping its texture
+    pub fn with_lumens(self, lumens: f64, area: f64) -> Self {
+        self.with_power(lumens / LUMENS_PER_WATT, area)
+    }
 }
 
 impl DiffuseLight<SolidColor> {
     /// Constructs a [DiffuseLight] material with a [SolidColor] as a texture
     pub fn from_color(color: Vec3) -> Self {
-        Self {
-            emit: SolidColor { color },
-        }
+        Self::new(SolidColor { color })
+    }
+
+    /// Constructs a [DiffuseLight] material with the color of a black body at
+    /// `temperature` (in kelvin) and the given luminance. Candles are around
+    /// 1900K, incandescent bulbs 2700K and daylight 6500K.
+    pub fn from_temperature(temperature: f64, luminance: f64) -> Self {
+        Self::from_color(blackbody(temperature) * luminance)
     }
 }
 
@@ -23,7 +111,64 @@ impl<T: Texture> Material for DiffuseLight<T> {
         None
     }
 
-    fn emitted(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
-        self.emit.value(u, v, p)
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        let emits = match self.sides {
+            LightSides::Front => hit.front_face,
+            LightSides::Back => !hit.front_face,
+            LightSides::Both => true,
+        };
+        if emits {
+            self.intensity * self.emit.value_at(hit)
+        } else {
+            Vec3::zero()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+    use crate::texture::UvChecker;
+
+    #[test]
+    fn power_gives_the_radiance_of_the_area() {
+        let light = DiffuseLight {
+            sides: LightSides::Front,
+            ..DiffuseLight::from_color(Vec3::one())
+        }
+        .with_power(10.0, 2.0);
+        let (_, hit) = hit_from(Vec3::new(0.0, 0.0, 1.0), &light);
+        let expected = 10.0 / (PI * 2.0);
+        assert!((light.emitted(&hit) - expected * Vec3::one()).length() < 1e-9);
+
+        // The same power is split between both faces
+        let both = DiffuseLight::from_color(Vec3::one()).with_power(10.0, 2.0);
+        assert!((both.intensity - expected / 2.0).abs() < 1e-9);
+
+        let lumens = DiffuseLight::from_color(Vec3::one()).with_lumens(LUMENS_PER_WATT, 2.0);
+        assert!((lumens.intensity - 1.0 / (PI * 4.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn front_light_is_dark_from_behind() {
+        let light = DiffuseLight {
+            sides: LightSides::Front,
+            ..DiffuseLight::from_color(Vec3::one())
+        };
+        let (_, front) = hit_from(Vec3::new(0.0, 0.0, 1.0), &light);
+        let (_, back) = hit_from(Vec3::new(0.0, 0.0, -1.0), &light);
+        assert!(front.front_face && !back.front_face);
+        assert_eq!(light.emitted(&front), Vec3::one());
+        assert_eq!(light.emitted(&back), Vec3::zero());
+    }
+
+    #[test]
+    fn power_of_a_textured_light() {
+        // Half of the surface is black, so the other half is twice as bright
+        let checker = UvChecker::from_colors(Vec3::one(), Vec3::zero(), (2.0, 1.0));
+        let light = DiffuseLight::new(checker).with_power(10.0, 2.0);
+        let white = DiffuseLight::from_color(Vec3::one()).with_power(10.0, 2.0);
+        assert!((light.intensity - 2.0 * white.intensity).abs() < 1e-9);
     }
 }
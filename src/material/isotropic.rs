@@ -1,5 +1,8 @@
 use super::*;
 
+/// Probability density of picking any direction uniformly from the unit sphere
+const UNIFORM_SPHERE_PDF: f64 = 1.0 / (4.0 * std::f64::consts::PI);
+
 /// Isotropic material
 ///
 /// Currently used as a smoke approximation as it scatters light
@@ -21,8 +24,24 @@ impl Isotropic<SolidColor> {
 
 impl<T: Texture> Material for Isotropic<T> {
     fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
-        let attenuation = self.albedo.value(hit.u, hit.v, &hit.point);
+        let attenuation = self.albedo.value_at(hit);
         let scattered = Ray::new(hit.point, Vec3::random_in_unit_sphere(), r_in.time);
         Some((attenuation, scattered))
     }
+
+    fn eval(&self, _r_in: &Ray, hit: &HitRecord, _direction: &Vec3) -> Vec3 {
+        self.albedo.value_at(hit) * UNIFORM_SPHERE_PDF
+    }
+
+    fn scattering_pdf(&self, _r_in: &Ray, _hit: &HitRecord, _direction: &Vec3) -> f64 {
+        UNIFORM_SPHERE_PDF
+    }
+
+    fn is_specular(&self, _hit: &HitRecord) -> bool {
+        false
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
+    }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use std::f64::consts::FRAC_1_PI;
 
 /// Lambertian material
 ///
@@ -28,7 +29,20 @@ impl<T: Texture> Material for Lambertian<T> {
         }
 
         let scattered = Ray::new(hit.point, scatter_direction, r_in.time);
-        let attenuation = self.albedo.value(hit.u, hit.v, &hit.point);
+        let attenuation = self.albedo.value_at(hit);
         Some((attenuation, scattered))
     }
+
+    fn eval(&self, _r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        let cosine = hit.normal.dot(&direction.unit_vector()).max(0.0);
+        self.albedo.value_at(hit) * cosine * FRAC_1_PI
+    }
+
+    fn scattering_pdf(&self, _r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        hit.normal.dot(&direction.unit_vector()).max(0.0) * FRAC_1_PI
+    }
+
+    fn is_specular(&self, _hit: &HitRecord) -> bool {
+        false
+    }
 }
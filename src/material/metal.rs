@@ -2,25 +2,37 @@ use super::*;
 
 /// Metalic material
 #[derive(Clone)]
-pub struct Metal {
-    /// Color the material scatters
-    pub albedo: Vec3,
-    /// Fuzz applied in reflections [`0.0`, `1.0`]
-    pub fuzz: f64,
+pub struct Metal<A: Texture, F: Texture> {
+    /// Texture with the color the material scatters
+    pub albedo: A,
+    /// Texture with the fuzz applied in reflections [`0.0`, `1.0`]. Only its
+    /// first channel is used.
+    pub fuzz: F,
 }
 
-impl Material for Metal {
+impl Metal<SolidColor, SolidColor> {
+    /// Constructs a [Metal] material with a [SolidColor] as albedo and the
+    /// same fuzz everywhere
+    pub fn from_color(albedo: Vec3, fuzz: f64) -> Self {
+        Self {
+            albedo: SolidColor { color: albedo },
+            fuzz: SolidColor::gray(fuzz),
+        }
+    }
+}
+
+impl<A: Texture, F: Texture> Material for Metal<A, F> {
     fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
         let reflected = reflect(&r_in.direction.unit_vector(), &hit.normal);
 
-        let fuzz = self.fuzz.min(1.0);
+        let fuzz = self.fuzz.value_at(hit).x().min(1.0);
 
         let scattered = Ray::new(
             hit.point,
             reflected + fuzz * Vec3::random_in_unit_sphere(),
             r_in.time,
         );
-        let attenuation = self.albedo;
+        let attenuation = self.albedo.value_at(hit);
         if scattered.direction.dot(&hit.normal) > 0.0 {
             Some((attenuation, scattered))
         } else {
@@ -28,3 +40,45 @@ impl Material for Metal {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+    use crate::texture::UvChecker;
+
+    #[test]
+    fn textured_albedo() {
+        let red = Vec3::new(0.9, 0.1, 0.1);
+        let blue = Vec3::new(0.1, 0.1, 0.9);
+        let metal = Metal {
+            albedo: UvChecker::from_colors(red, blue, (2.0, 1.0)),
+            fuzz: SolidColor::gray(0.0),
+        };
+        let (r, mut hit) = hit_from(Vec3::new(1.0, 0.0, 1.0), &metal);
+        hit.u = 0.25;
+        assert_eq!(metal.scatter(&r, &hit).unwrap().0, blue);
+        hit.u = 0.75;
+        assert_eq!(metal.scatter(&r, &hit).unwrap().0, red);
+    }
+
+    #[test]
+    fn textured_fuzz() {
+        let metal = Metal {
+            albedo: SolidColor::gray(1.0),
+            fuzz: UvChecker::from_colors(Vec3::splat(0.5), Vec3::zero(), (2.0, 1.0)),
+        };
+        let (r, mut hit) = hit_from(Vec3::new(1.0, 0.0, 1.0), &metal);
+        let mirror = Vec3::new(-1.0, 0.0, 1.0).unit_vector();
+        let deviation = |hit: &HitRecord| {
+            (0..100)
+                .filter_map(|_| metal.scatter(&r, hit))
+                .map(|(_, scattered)| (scattered.direction.unit_vector() - mirror).length())
+                .fold(0.0, f64::max)
+        };
+        hit.u = 0.25;
+        assert!(deviation(&hit) < 1e-9);
+        hit.u = 0.75;
+        assert!(deviation(&hit) > 0.01);
+    }
+}
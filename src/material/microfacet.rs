@@ -0,0 +1,207 @@
+use super::*;
+use std::f64::consts::{PI, TAU};
+
+/// GGX (Trowbridge-Reitz) microfacet distribution
+///
+/// Describes how the normals of the tiny facets of a rough surface are
+/// distributed. Directions are in the local space of the surface, where
+/// `z` is the normal.
+#[derive(Clone, Copy)]
+pub struct GGX {
+    /// Roughness along the `x` axis of the local space
+    pub alpha_x: f64,
+    /// Roughness along the `y` axis of the local space
+    pub alpha_y: f64,
+}
+
+/// Below this alpha the surface is considered a perfect mirror
+const SMOOTH_ALPHA: f64 = 1e-3;
+
+impl GGX {
+    /// Creates a distribution with the same `roughness` [`0.0`, `1.0`] in
+    /// every direction
+    pub fn isotropic(roughness: f64) -> Self {
+        Self::anisotropic(roughness, roughness)
+    }
+
+    /// Creates a distribution with a different roughness [`0.0`, `1.0`]
+    /// along each tangent axis of the surface
+    pub fn anisotropic(roughness_x: f64, roughness_y: f64) -> Self {
+        // Squaring the roughness makes it perceptually linear
+        GGX {
+            alpha_x: roughness_x * roughness_x,
+            alpha_y: roughness_y * roughness_y,
+        }
+    }
+
+    /// Returns `true` if the distribution is so narrow the surface is
+    /// better treated as perfectly smooth
+    pub fn is_smooth(&self) -> bool {
+        self.alpha_x.max(self.alpha_y) < SMOOTH_ALPHA
+    }
+
+    /// Density of microfacets with normal `wh`
+    pub fn d(&self, wh: &Vec3) -> f64 {
+        if wh.z() <= 0.0 {
+            return 0.0;
+        }
+        let x = wh.x() / self.alpha_x;
+        let y = wh.y() / self.alpha_y;
+        let t = x * x + y * y + wh.z() * wh.z();
+        1.0 / (PI * self.alpha_x * self.alpha_y * t * t)
+    }
+
+    /// Smith's auxiliary function, used to compute the masking
+    fn lambda(&self, w: &Vec3) -> f64 {
+        let z2 = w.z() * w.z();
+        if z2 == 0.0 {
+            return f64::INFINITY;
+        }
+        let x = w.x() * self.alpha_x;
+        let y = w.y() * self.alpha_y;
+        (((x * x + y * y) / z2 + 1.0).sqrt() - 1.0) * 0.5
+    }
+
+    /// Fraction of microfacets visible from direction `w`
+    pub fn g1(&self, w: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// Fraction of microfacets visible from both `wo` and `wi`
+    /// (height-correlated Smith masking-shadowing)
+    pub fn g(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Samples a microfacet normal visible from `wo`, following Heitz's
+    /// "Sampling the GGX Distribution of Visible Normals". `wo` has to be
+    /// above the surface.
+    pub fn sample_wh(&self, wo: &Vec3) -> Vec3 {
+        // Stretch the view direction to the hemisphere configuration
+        let vh = Vec3::new(self.alpha_x * wo.x(), self.alpha_y * wo.y(), wo.z()).unit_vector();
+        let len2 = vh.x() * vh.x() + vh.y() * vh.y();
+        let t1 = if len2 > 0.0 {
+            Vec3::new(-vh.y(), vh.x(), 0.0) / len2.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = vh.cross(&t1);
+
+        // Uniform point in the projected area of the hemisphere
+        let r = rand::random::<f64>().sqrt();
+        let phi = TAU * rand::random::<f64>();
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + vh.z());
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let nh = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * vh;
+
+        // Unstretch
+        Vec3::new(
+            self.alpha_x * nh.x(),
+            self.alpha_y * nh.y(),
+            nh.z().max(1e-6),
+        )
+        .unit_vector()
+    }
+
+    /// Probability density (with respect to solid angle) of
+    /// [sample_wh](GGX::sample_wh) returning `wh` for `wo`
+    pub fn pdf(&self, wo: &Vec3, wh: &Vec3) -> f64 {
+        if wo.z() <= 0.0 {
+            return 0.0;
+        }
+        self.g1(wo) * wo.dot(wh).max(0.0) * self.d(wh) / wo.z()
+    }
+}
+
+/// Fresnel reflectance of a conductor with complex refraction index
+/// `eta + i k` for light arriving with angle cosine `cos_theta`
+fn fresnel_conductor_channel(cos_theta: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos_theta * cos_theta;
+    let sin2 = 1.0 - cos2;
+    let eta2 = eta * eta;
+    let k2 = k * k;
+
+    let t0 = eta2 - k2 - sin2;
+    let a2_plus_b2 = (t0 * t0 + 4.0 * eta2 * k2).sqrt();
+    let t1 = a2_plus_b2 + cos2;
+    let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t2 = 2.0 * cos_theta * a;
+    let rs = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+
+    0.5 * (rp + rs)
+}
+
+/// Fresnel reflectance of a conductor with complex refraction index
+/// `eta + i k` (one value per color channel)
+pub fn fresnel_conductor(cos_theta: f64, eta: &Vec3, k: &Vec3) -> Vec3 {
+    let cos_theta = cos_theta.clamp_(0.0, 1.0);
+    eta.zip_with(*k, |eta, k| fresnel_conductor_channel(cos_theta, eta, k))
+}
+
+/// Fresnel reflectance of an interface between two dielectrics for light
+/// arriving with angle cosine `cos_theta`. `eta` is the refraction index at
+/// the other side of the interface relative to the one at the side of the light.
+pub fn fresnel_dielectric(cos_theta: f64, eta: f64) -> f64 {
+    let cos_i = cos_theta.clamp_(0.0, 1.0);
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+    // Total internal reflection
+    if sin2_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    let r_parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let r_perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    0.5 * (r_parallel * r_parallel + r_perpendicular * r_perpendicular)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ggx_is_normalized() {
+        // The projected area of the microfacets is the one of the surface
+        for ggx in [GGX::isotropic(0.5), GGX::anisotropic(0.3, 0.8)] {
+            let n = 400;
+            let d_theta = 0.5 * PI / n as f64;
+            let d_phi = TAU / n as f64;
+            let mut area = 0.0;
+            for i in 0..n {
+                let theta = (i as f64 + 0.5) * d_theta;
+                for j in 0..n {
+                    let phi = (j as f64 + 0.5) * d_phi;
+                    let wh = Vec3::new(
+                        theta.sin() * phi.cos(),
+                        theta.sin() * phi.sin(),
+                        theta.cos(),
+                    );
+                    area += ggx.d(&wh) * wh.z() * theta.sin() * d_theta * d_phi;
+                }
+            }
+            assert!((area - 1.0).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn conductor_fresnel() {
+        let eta = Vec3::new(0.2, 0.9, 1.1);
+        let k = Vec3::new(3.9, 2.5, 2.1);
+        let normal = fresnel_conductor(1.0, &eta, &k);
+        let grazing = fresnel_conductor(0.0, &eta, &k);
+        // Normal incidence matches ((n-1)² + k²) / ((n+1)² + k²)
+        let expected = ((0.2f64 - 1.0).powi(2) + 3.9 * 3.9) / (1.2f64.powi(2) + 3.9 * 3.9);
+        assert!((normal.x() - expected).abs() < 1e-9);
+        assert!((grazing - Vec3::one()).length() < 1e-9);
+    }
+
+    #[test]
+    fn dielectric_fresnel() {
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-9);
+        assert_eq!(fresnel_dielectric(0.1, 1.0 / 1.5), 1.0);
+    }
+}
@@ -0,0 +1,126 @@
+use super::*;
+
+/// Mix of two materials
+///
+/// On every hit one of the two materials is chosen at random, with the
+/// probability of the `second` given by the `weight` texture (its first
+/// channel). Useful for rust on metal, dirt on a floor...
+#[derive(Clone)]
+pub struct Mix<A: Material, B: Material, T: Texture> {
+    /// Material used where the weight is `0.0`
+    pub first: A,
+    /// Material used where the weight is `1.0`
+    pub second: B,
+    /// Texture with the probability [`0.0`, `1.0`] of using the `second` material
+    pub weight: T,
+}
+
+impl<A: Material, B: Material, T: Texture> Mix<A, B, T> {
+    /// Returns the probability of using the `second` material at the hit
+    fn weight(&self, hit: &HitRecord) -> f64 {
+        self.weight.value_at(hit).x().clamp_(0.0, 1.0)
+    }
+}
+
+impl<A: Material, B: Material, T: Texture> Material for Mix<A, B, T> {
+    fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
+        if self.weight(hit) > rand::random() {
+            self.second.scatter(r_in, hit)
+        } else {
+            self.first.scatter(r_in, hit)
+        }
+    }
+
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        let weight = self.weight(hit);
+        (1.0 - weight) * self.first.emitted(hit) + weight * self.second.emitted(hit)
+    }
+
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        let weight = self.weight(hit);
+        (1.0 - weight) * self.first.eval(r_in, hit, direction)
+            + weight * self.second.eval(r_in, hit, direction)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        let weight = self.weight(hit);
+        (1.0 - weight) * self.first.scattering_pdf(r_in, hit, direction)
+            + weight * self.second.scattering_pdf(r_in, hit, direction)
+    }
+
+    /// The mix is specular if any of the materials that can be chosen is,
+    /// so the hit is only sampled through [scatter](Material::scatter)
+    fn is_specular(&self, hit: &HitRecord) -> bool {
+        let weight = self.weight(hit);
+        (weight < 1.0 && self.first.is_specular(hit))
+            || (weight > 0.0 && self.second.is_specular(hit))
+    }
+
+    fn is_volumetric(&self) -> bool {
+        self.first.is_volumetric() && self.second.is_volumetric()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+
+    #[test]
+    fn eval_and_pdf_are_weighted() {
+        let first = Lambertian::from_color(Vec3::new(0.8, 0.1, 0.1));
+        let second = Principled {
+            roughness: Arc::new(SolidColor::gray(0.2)),
+            ..Principled::from_color(Vec3::new(0.1, 0.1, 0.8))
+        };
+        let mix = Mix {
+            first: first.clone(),
+            second: second.clone(),
+            weight: SolidColor::gray(0.25),
+        };
+
+        let (r, hit) = hit_from(Vec3::new(1.0, 0.0, 1.0), &mix);
+        for direction in [
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(-1.0, 0.0, 1.0),
+            Vec3::new(0.3, 0.5, 0.2),
+        ] {
+            let expected =
+                0.75 * first.eval(&r, &hit, &direction) + 0.25 * second.eval(&r, &hit, &direction);
+            assert!((mix.eval(&r, &hit, &direction) - expected).length() < 1e-12);
+
+            let expected = 0.75 * first.scattering_pdf(&r, &hit, &direction)
+                + 0.25 * second.scattering_pdf(&r, &hit, &direction);
+            assert!((mix.scattering_pdf(&r, &hit, &direction) - expected).abs() < 1e-12);
+        }
+    }
+
+    /// Weight that is only right when read at a hit, like a projection
+    struct HitOnlyWeight;
+
+    impl Texture for HitOnlyWeight {
+        fn value(&self, _u: f64, _v: f64, _p: &Vec3) -> Vec3 {
+            Vec3::zero()
+        }
+
+        fn value_at(&self, _hit: &HitRecord) -> Vec3 {
+            Vec3::one()
+        }
+    }
+
+    #[test]
+    fn weight_is_read_at_the_hit() {
+        let second = Lambertian::from_color(Vec3::new(0.1, 0.1, 0.8));
+        let mix = Mix {
+            first: Lambertian::from_color(Vec3::new(0.8, 0.1, 0.1)),
+            second: second.clone(),
+            weight: HitOnlyWeight,
+        };
+        let (r, hit) = hit_from(Vec3::new(1.0, 0.0, 1.0), &mix);
+        let direction = Vec3::new(0.0, 0.0, 1.0);
+        assert_eq!(
+            mix.eval(&r, &hit, &direction),
+            second.eval(&r, &hit, &direction)
+        );
+    }
+}
@@ -6,9 +6,12 @@
 //! functions are exposed to be used if wanted.
 
 use crate::hittable::HitRecord;
+use crate::onb::ONB;
 use crate::ray::Ray;
 use crate::texture::{SolidColor, Texture};
+use crate::Clampable;
 use std::ops::Neg;
+use std::sync::Arc;
 use vec3::Vec3;
 
 /// The [Material] trait has to be implemented for every material type.
@@ -19,11 +22,67 @@ pub trait Material: Send + Sync {
     /// and a new [Ray] with the new direction and origin.
     fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)>;
     #[allow(unused)]
-    /// Returns the light emitted by the material. It has a default implementation
+    /// Returns the light emitted by the material at a hit, towards the side
+    /// the hit is seen from (its [front_face](HitRecord::front_face) tells
+    /// which face of the surface it is). It has a default implementation
     /// where the material does not emit light.
-    fn emitted(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
         Vec3::zero()
     }
+    #[allow(unused)]
+    /// Evaluates the scattering function (the BSDF times the cosine of the
+    /// angle with the normal) for light that leaves the hit in `direction`.
+    /// Only meaningful for materials that are not [specular](Material::is_specular).
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        Vec3::zero()
+    }
+    #[allow(unused)]
+    /// Returns the probability density (with respect to solid angle) of
+    /// [scatter](Material::scatter) generating a ray in `direction`.
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        0.0
+    }
+    #[allow(unused)]
+    /// Returns `true` if the material scatters light only in a few discrete
+    /// directions (mirrors, glass...), so it cannot be evaluated for an
+    /// arbitrary direction with [eval](Material::eval). It has a default
+    /// implementation where the material is specular.
+    fn is_specular(&self, hit: &HitRecord) -> bool {
+        true
+    }
+    /// Returns `true` if the material is the phase function of a participating
+    /// medium, that scatters light inside a volume instead of on a surface.
+    fn is_volumetric(&self) -> bool {
+        false
+    }
+}
+
+/// Shared materials, so many objects can use the same material without
+/// copying it. Allows using `Arc<dyn Material>` where a [Material] is expected.
+impl<M: Material + ?Sized> Material for Arc<M> {
+    fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
+        (**self).scatter(r_in, hit)
+    }
+
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        (**self).emitted(hit)
+    }
+
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        (**self).eval(r_in, hit, direction)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        (**self).scattering_pdf(r_in, hit, direction)
+    }
+
+    fn is_specular(&self, hit: &HitRecord) -> bool {
+        (**self).is_specular(hit)
+    }
+
+    fn is_volumetric(&self) -> bool {
+        (**self).is_volumetric()
+    }
 }
 
 /// Returns the direction of a reflected ray of light on a hit.
@@ -51,9 +110,77 @@ mod lambertian;
 pub use lambertian::*;
 mod metal;
 pub use metal::*;
+mod microfacet;
+pub use microfacet::*;
+mod thin_film;
+pub use thin_film::*;
+mod conductor;
+pub use conductor::*;
 mod dielectric;
 pub use dielectric::*;
+mod principled;
+pub use principled::*;
+mod coated;
+pub use coated::*;
+mod mix;
+pub use mix::*;
+mod two_sided;
+pub use two_sided::*;
+mod translucent;
+pub use translucent::*;
+mod normal_map;
+pub use normal_map::*;
+mod bump_map;
+pub use bump_map::*;
 mod diffuse_light;
 pub use diffuse_light::*;
 mod isotropic;
 pub use isotropic::*;
+
+/// Helpers shared by the tests of the materials
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// Hit at the origin of a surface with normal `+z` and tangents `+x`
+    /// and `+y`, reached by a ray coming from `from`
+    pub(crate) fn hit_from(from: Vec3, material: &dyn Material) -> (Ray, HitRecord<'_>) {
+        let r = Ray::new(from, -from, 0.0);
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let hit = HitRecord::new(&r, 1.0, 0.5, 0.5, Vec3::zero(), normal, material)
+            .with_tangents(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        (r, hit)
+    }
+
+    /// Fraction of the light arriving from `from` that the material scatters,
+    /// estimated with `samples` calls to [scatter](Material::scatter)
+    pub(crate) fn albedo(material: &dyn Material, from: Vec3, samples: usize) -> Vec3 {
+        let (r, hit) = hit_from(from, material);
+        let total: Vec3 = (0..samples)
+            .filter_map(|_| material.scatter(&r, &hit))
+            .map(|(attenuation, _)| attenuation)
+            .sum();
+        total / samples as f64
+    }
+
+    /// Checks that the attenuation returned by [scatter](Material::scatter)
+    /// is [eval](Material::eval) over [scattering_pdf](Material::scattering_pdf)
+    pub(crate) fn assert_scatter_matches_eval(material: &dyn Material, from: Vec3) {
+        let (r, hit) = hit_from(from, material);
+        for _ in 0..200 {
+            let (attenuation, scattered) = match material.scatter(&r, &hit) {
+                Some(s) => s,
+                None => continue,
+            };
+            let f = material.eval(&r, &hit, &scattered.direction);
+            let pdf = material.scattering_pdf(&r, &hit, &scattered.direction);
+            assert!(pdf > 0.0);
+            assert!(
+                (f / pdf - attenuation).length() <= 1e-6 * attenuation.length().max(1.0),
+                "{:?} != {:?}",
+                f / pdf,
+                attenuation
+            );
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::*;
+
+/// Tangent-space normal map
+///
+/// Wraps another material, shading it with the normals stored as colors in
+/// a texture (usually an [ImageTexture](crate::texture::ImageTexture)
+/// loaded with [data](crate::texture::ImageTexture::data)). The
+/// red channel goes along the tangent (`u`), the green one along the
+/// bitangent (`v`) and the blue one along the normal of the surface.
+#[derive(Clone)]
+pub struct NormalMap<M: Material, T: Texture> {
+    /// Material shaded with the new normals
+    pub material: M,
+    /// Texture with the normals encoded as colors in [`0.0`, `1.0`]
+    pub map: T,
+    /// Multiplies the deviation from the surface normal. `1.0` uses the map as is.
+    pub strength: f64,
+}
+
+impl<M: Material, T: Texture> NormalMap<M, T> {
+    /// Returns the hit shaded with the normal of the map. Rays that would
+    /// arrive from below the new normal keep the original hit.
+    fn shade<'a>(&self, r_in: &Ray, hit: &HitRecord<'a>) -> HitRecord<'a> {
+        let color = self.map.value_at(hit);
+        let local = Vec3::new(
+            (2.0 * color.x() - 1.0) * self.strength,
+            (2.0 * color.y() - 1.0) * self.strength,
+            2.0 * color.z() - 1.0,
+        );
+
+        let normal = hit.normal;
+        let tangent = (hit.dpdu - normal.dot(&hit.dpdu) * normal).unit_vector();
+        let mut bitangent = normal.cross(&tangent);
+        if bitangent.dot(&hit.dpdv) < 0.0 {
+            bitangent = -bitangent;
+        }
+
+        let shading_normal = local.x() * tangent + local.y() * bitangent + local.z() * normal;
+        if shading_normal.near_zero() || shading_normal.dot(&r_in.direction) >= 0.0 {
+            return hit.clone();
+        }
+        hit.with_shading_normal(shading_normal)
+    }
+}
+
+impl<M: Material, T: Texture> Material for NormalMap<M, T> {
+    fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.material.scatter(r_in, &self.shade(r_in, hit))
+    }
+
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        self.material.emitted(hit)
+    }
+
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        self.material.eval(r_in, &self.shade(r_in, hit), direction)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        self.material
+            .scattering_pdf(r_in, &self.shade(r_in, hit), direction)
+    }
+
+    fn is_specular(&self, hit: &HitRecord) -> bool {
+        self.material.is_specular(hit)
+    }
+
+    fn is_volumetric(&self) -> bool {
+        self.material.is_volumetric()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+    use crate::texture::SolidColor;
+
+    #[test]
+    fn flat_map_keeps_the_normal() {
+        let material = NormalMap {
+            material: Lambertian::from_color(Vec3::one()),
+            map: SolidColor::rgb(0.5, 0.5, 1.0),
+            strength: 1.0,
+        };
+        let (r, hit) = hit_from(Vec3::new(1.0, 2.0, 3.0), &material);
+        let shaded = material.shade(&r, &hit);
+        assert!((shaded.normal - hit.normal).length() < 1e-9);
+    }
+
+    #[test]
+    fn red_tilts_the_normal_along_the_tangent() {
+        let material = NormalMap {
+            material: Lambertian::from_color(Vec3::one()),
+            map: SolidColor::rgb(1.0, 0.5, 1.0),
+            strength: 1.0,
+        };
+        let (r, hit) = hit_from(Vec3::new(0.0, 0.0, 1.0), &material);
+        let shaded = material.shade(&r, &hit);
+        let expected = Vec3::new(1.0, 0.0, 1.0).unit_vector();
+        assert!((shaded.normal - expected).length() < 1e-9);
+    }
+}
@@ -0,0 +1,399 @@
+use super::*;
+use std::f64::consts::FRAC_1_PI;
+use std::sync::Arc;
+
+/// Roughness of the clearcoat layer
+const CLEARCOAT_ROUGHNESS: f64 = 0.3;
+/// Lowest roughness, so every lobe can be evaluated in any direction
+const MIN_ROUGHNESS: f64 = 0.05;
+
+/// Principled material
+///
+/// Uber material in the style of the Disney BRDF. A single set of intuitive
+/// parameters covers plastics, metals, glass, cloth and lights. Every
+/// parameter is a [Texture]; the ones that are a single number read the
+/// first channel of theirs (see [SolidColor::gray]).
+///
+/// Unlike the other materials, the textures are behind an [Arc] instead of
+/// being generic: a type parameter for each of them would make the type
+/// unwieldy, and every parameter given would change the type, so the
+/// defaults could not be taken with `..Default::default()`. Textures from
+/// a [TextureCache](crate::texture::TextureCache) are shared as they are.
+#[derive(Clone)]
+pub struct Principled {
+    /// Color of the diffuse, metallic and transmitted light
+    pub base_color: Arc<dyn Texture>,
+    /// Blend between a dielectric (`0.0`) and a metal (`1.0`)
+    pub metallic: Arc<dyn Texture>,
+    /// Roughness of the specular reflection and transmission [`0.0`, `1.0`]
+    pub roughness: Arc<dyn Texture>,
+    /// Amount of dielectric specular reflection [`0.0`, `1.0`]. `0.5` is a
+    /// refraction index of `1.5`.
+    pub specular: Arc<dyn Texture>,
+    /// Tints the dielectric specular reflection towards the base color [`0.0`, `1.0`]
+    pub specular_tint: Arc<dyn Texture>,
+    /// Soft reflection at grazing angles, for cloth [`0.0`, `1.0`]
+    pub sheen: Arc<dyn Texture>,
+    /// Strength of a second glossy and white specular layer [`0.0`, `1.0`]
+    pub clearcoat: Arc<dyn Texture>,
+    /// Blend between an opaque (`0.0`) and a transparent (`1.0`) dielectric
+    pub transmission: Arc<dyn Texture>,
+    /// Light emitted by the material
+    pub emission: Arc<dyn Texture>,
+}
+
+impl Default for Principled {
+    /// Rough white plastic
+    fn default() -> Self {
+        Self {
+            base_color: Arc::new(SolidColor::gray(0.8)),
+            metallic: Arc::new(SolidColor::gray(0.0)),
+            roughness: Arc::new(SolidColor::gray(0.5)),
+            specular: Arc::new(SolidColor::gray(0.5)),
+            specular_tint: Arc::new(SolidColor::gray(0.0)),
+            sheen: Arc::new(SolidColor::gray(0.0)),
+            clearcoat: Arc::new(SolidColor::gray(0.0)),
+            transmission: Arc::new(SolidColor::gray(0.0)),
+            emission: Arc::new(SolidColor::gray(0.0)),
+        }
+    }
+}
+
+impl Principled {
+    /// Constructs a [Principled] material with a [SolidColor] as base color
+    /// and the default value of the other parameters
+    pub fn from_color(color: Vec3) -> Self {
+        Self {
+            base_color: Arc::new(SolidColor { color }),
+            ..Default::default()
+        }
+    }
+
+    /// Evaluates the textures of the parameters at the hit
+    fn params(&self, hit: &HitRecord) -> Params {
+        let scalar = |texture: &Arc<dyn Texture>| texture.value_at(hit).x().clamp_(0.0, 1.0);
+
+        let base_color = self.base_color.value_at(hit);
+        let metallic = scalar(&self.metallic);
+        let roughness = scalar(&self.roughness).max(MIN_ROUGHNESS);
+        let specular = scalar(&self.specular);
+        let specular_tint = scalar(&self.specular_tint);
+        let transmission = scalar(&self.transmission);
+
+        let luminance = base_color.luminance();
+        let tint = if luminance > 0.0 {
+            base_color / luminance
+        } else {
+            Vec3::one()
+        };
+        let dielectric_f0 = 0.08 * specular;
+        let specular_color = dielectric_f0 * lerp(Vec3::one(), tint, specular_tint);
+        let sqrt_f0 = dielectric_f0.max(1e-3).sqrt();
+        let index_refraction = (1.0 + sqrt_f0) / (1.0 - sqrt_f0);
+
+        Params {
+            base_color,
+            metallic,
+            transmission,
+            sheen: scalar(&self.sheen),
+            sheen_color: lerp(Vec3::one(), tint, 0.5),
+            clearcoat: scalar(&self.clearcoat),
+            f0: lerp(specular_color, base_color, metallic),
+            eta: if hit.front_face {
+                index_refraction
+            } else {
+                1.0 / index_refraction
+            },
+            roughness,
+            distribution: GGX::isotropic(roughness),
+            clearcoat_distribution: GGX::isotropic(CLEARCOAT_ROUGHNESS),
+        }
+    }
+}
+
+/// Linear interpolation between `a` and `b`
+fn lerp(a: Vec3, b: Vec3, t: f64) -> Vec3 {
+    (1.0 - t) * a + t * b
+}
+
+/// Schlick's weight `(1 - cos)⁵` of the Fresnel approximation
+fn schlick_weight(cosine: f64) -> f64 {
+    (1.0 - cosine.clamp_(0.0, 1.0)).powi(5)
+}
+
+/// Schlick's approximation of the Fresnel reflectance
+fn schlick(f0: Vec3, cosine: f64) -> Vec3 {
+    lerp(f0, Vec3::one(), schlick_weight(cosine))
+}
+
+/// Parameters of a [Principled] material at a hit
+struct Params {
+    base_color: Vec3,
+    metallic: f64,
+    transmission: f64,
+    sheen: f64,
+    sheen_color: Vec3,
+    clearcoat: f64,
+    /// Reflectance at normal incidence of the specular lobe
+    f0: Vec3,
+    /// Refraction index at the other side of the surface relative to the
+    /// side of the incoming ray
+    eta: f64,
+    roughness: f64,
+    distribution: GGX,
+    clearcoat_distribution: GGX,
+}
+
+impl Params {
+    /// Probability of sampling the diffuse, specular, transmission and
+    /// clearcoat lobes
+    fn lobe_probabilities(&self) -> [f64; 4] {
+        let dielectric = 1.0 - self.metallic;
+        let diffuse = dielectric * (1.0 - self.transmission);
+        let specular = 1.0 - 0.75 * diffuse;
+        let transmission = dielectric * self.transmission;
+        let clearcoat = 0.25 * self.clearcoat;
+        let total = diffuse + specular + transmission + clearcoat;
+        [
+            diffuse / total,
+            specular / total,
+            transmission / total,
+            clearcoat / total,
+        ]
+    }
+
+    /// Scattering function (times the cosine) from `wo` to `wi`, in the
+    /// local space of the hit
+    fn eval(&self, wo: &Vec3, wi: &Vec3) -> Vec3 {
+        let dielectric = 1.0 - self.metallic;
+        let transmissive = dielectric * self.transmission;
+
+        if wi.z() > 0.0 {
+            let wh = (*wo + *wi).unit_vector();
+            let cos_d = wi.dot(&wh);
+
+            // Burley's diffuse with retroreflection and sheen, with only
+            // the light the specular reflection leaves at both sides
+            let fd90 = 0.5 + 2.0 * self.roughness * cos_d * cos_d;
+            let fd = (1.0 + (fd90 - 1.0) * schlick_weight(wo.z()))
+                * (1.0 + (fd90 - 1.0) * schlick_weight(wi.z()));
+            let diffuse = (self.base_color * (fd * FRAC_1_PI)
+                + self.sheen_color * (self.sheen * schlick_weight(cos_d)))
+                * (Vec3::one() - schlick(self.f0, wo.z()))
+                * (Vec3::one() - schlick(self.f0, wi.z()))
+                * (dielectric * (1.0 - self.transmission));
+
+            // Opaque surfaces use Schlick, transmissive ones the exact
+            // Fresnel term to have total internal reflection
+            let fresnel = schlick(self.f0, cos_d) * (1.0 - transmissive)
+                + Vec3::splat(fresnel_dielectric(cos_d, self.eta) * transmissive);
+            let specular =
+                fresnel * (self.distribution.d(&wh) * self.distribution.g(wo, wi) / (4.0 * wo.z()));
+
+            let clearcoat = 0.25
+                * self.clearcoat
+                * schlick(Vec3::splat(0.04), cos_d).x()
+                * self.clearcoat_distribution.d(&wh)
+                * self.clearcoat_distribution.g(wo, wi)
+                / (4.0 * wo.z());
+
+            diffuse * wi.z() + specular + Vec3::splat(clearcoat)
+        } else {
+            let (wh, cos_o, cos_i) = match self.refraction_half_vector(wo, wi) {
+                Some(h) => h,
+                None => return Vec3::zero(),
+            };
+            let fresnel = fresnel_dielectric(cos_o, self.eta);
+            let denom = cos_o + self.eta * cos_i;
+            // Light crosses two interfaces in a closed object
+            let tint = self.base_color.map(f64::sqrt);
+            tint * (transmissive
+                * (1.0 - fresnel)
+                * self.distribution.d(&wh)
+                * self.distribution.g(wo, wi)
+                * self.eta
+                * self.eta
+                * (cos_i * cos_o).abs()
+                / (wo.z() * denom * denom))
+        }
+    }
+
+    /// Half vector of a refraction from `wo` to `wi` and the cosines of
+    /// both directions with it. [None] if the refraction is not possible.
+    fn refraction_half_vector(&self, wo: &Vec3, wi: &Vec3) -> Option<(Vec3, f64, f64)> {
+        let mut wh = (*wo + self.eta * *wi).unit_vector();
+        if wh.z() < 0.0 {
+            wh = wh.neg();
+        }
+        let cos_o = wo.dot(&wh);
+        let cos_i = wi.dot(&wh);
+        if cos_o <= 0.0 || cos_i >= 0.0 {
+            None
+        } else {
+            Some((wh, cos_o, cos_i))
+        }
+    }
+
+    /// Probability density (with respect to solid angle) of [sample](Params::sample)
+    /// returning `wi`
+    fn pdf(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        let [diffuse, specular, transmission, clearcoat] = self.lobe_probabilities();
+        if wi.z() > 0.0 {
+            let wh = (*wo + *wi).unit_vector();
+            let reflection = 1.0 / (4.0 * wo.dot(&wh));
+            diffuse * wi.z() * FRAC_1_PI
+                + specular * self.distribution.pdf(wo, &wh) * reflection
+                + clearcoat * self.clearcoat_distribution.pdf(wo, &wh) * reflection
+        } else {
+            match self.refraction_half_vector(wo, wi) {
+                Some((wh, cos_o, cos_i)) => {
+                    let denom = cos_o + self.eta * cos_i;
+                    transmission
+                        * self.distribution.pdf(wo, &wh)
+                        * self.eta
+                        * self.eta
+                        * cos_i.abs()
+                        / (denom * denom)
+                }
+                None => 0.0,
+            }
+        }
+    }
+
+    /// Samples a direction choosing one of the lobes at random
+    fn sample(&self, wo: &Vec3) -> Option<Vec3> {
+        let [diffuse, specular, transmission, _] = self.lobe_probabilities();
+        let choice: f64 = rand::random();
+
+        if choice < diffuse {
+            Some(Vec3::random_cosine_direction())
+        } else if choice < diffuse + specular {
+            let wh = self.distribution.sample_wh(wo);
+            Some(reflect(&wo.neg(), &wh))
+        } else if choice < diffuse + specular + transmission {
+            let wh = self.distribution.sample_wh(wo);
+            let cos_o = wo.dot(&wh);
+            // Total internal reflection
+            if (1.0 - cos_o * cos_o) >= self.eta * self.eta {
+                return None;
+            }
+            Some(refract(&wo.neg(), &wh, 1.0 / self.eta))
+        } else {
+            let wh = self.clearcoat_distribution.sample_wh(wo);
+            Some(reflect(&wo.neg(), &wh))
+        }
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let params = self.params(hit);
+        let wi = params.sample(&wo)?;
+        let pdf = params.pdf(&wo, &wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        let scattered = Ray::new(hit.point, onb.local_vec(&wi), r_in.time);
+        Some((params.eval(&wo, &wi) / pdf, scattered))
+    }
+
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        self.emission.value_at(hit)
+    }
+
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        let wi = onb.to_local(&direction.unit_vector());
+        if wo.z() <= 0.0 || wi.z() == 0.0 {
+            return Vec3::zero();
+        }
+        self.params(hit).eval(&wo, &wi)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        let onb = ONB::build_from_wu(&hit.normal, &hit.dpdu);
+        let wo = onb.to_local(&r_in.direction.unit_vector().neg());
+        let wi = onb.to_local(&direction.unit_vector());
+        if wo.z() <= 0.0 || wi.z() == 0.0 {
+            return 0.0;
+        }
+        self.params(hit).pdf(&wo, &wi)
+    }
+
+    fn is_specular(&self, _hit: &HitRecord) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+
+    fn gray(value: f64) -> Arc<dyn Texture> {
+        Arc::new(SolidColor::gray(value))
+    }
+
+    /// Incoming directions from the normal to near grazing
+    fn directions() -> [Vec3; 3] {
+        [
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 0.2),
+        ]
+    }
+
+    #[test]
+    fn scatter_matches_eval() {
+        let materials = [
+            Principled::default(),
+            Principled {
+                metallic: gray(1.0),
+                roughness: gray(0.2),
+                ..Principled::from_color(Vec3::new(0.9, 0.6, 0.3))
+            },
+            Principled {
+                sheen: gray(1.0),
+                clearcoat: gray(1.0),
+                specular_tint: gray(0.5),
+                ..Principled::from_color(Vec3::new(0.2, 0.5, 0.8))
+            },
+            Principled {
+                transmission: gray(1.0),
+                roughness: gray(0.3),
+                ..Default::default()
+            },
+        ];
+        for material in &materials {
+            for from in directions() {
+                assert_scatter_matches_eval(material, from);
+            }
+        }
+    }
+
+    #[test]
+    fn white_conserves_energy() {
+        for metallic in [0.0, 1.0] {
+            for roughness in [0.05, 0.5, 1.0] {
+                let material = Principled {
+                    metallic: gray(metallic),
+                    roughness: gray(roughness),
+                    ..Principled::from_color(Vec3::one())
+                };
+                for from in directions() {
+                    // With some room for the noise of the estimate
+                    let albedo = albedo(&material, from, 20000);
+                    assert!(albedo.reduce(f64::max) <= 1.01, "{:?}", albedo);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use super::*;
+use crate::spectrum::{cie_xyz, rgb_to_spectrum, xyz_to_rgb, LAMBDA_MAX, LAMBDA_MIN};
+use std::f64::consts::PI;
+use std::ops::{Add, Div, Mul, Sub};
+use std::sync::{Arc, OnceLock};
+
+/// Number of wavelengths the reflectance is evaluated at to find its color
+const FILM_WAVELENGTHS: usize = 32;
+
+/// Complex number, just what the Fresnel equations of a film need
+#[derive(Clone, Copy, Debug)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    fn real(re: f64) -> Self {
+        Self::new(re, 0.0)
+    }
+
+    /// `e^(i phase)`
+    fn from_phase(phase: f64) -> Self {
+        Self::new(phase.cos(), phase.sin())
+    }
+
+    fn norm_squared(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    /// Principal square root
+    fn sqrt(self) -> Self {
+        let norm = self.norm_squared().sqrt();
+        let re = (0.5 * (norm + self.re)).max(0.0).sqrt();
+        let im = (0.5 * (norm - self.re)).max(0.0).sqrt();
+        Self::new(re, if self.im < 0.0 { -im } else { im })
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        let norm = other.norm_squared();
+        Self::new(
+            (self.re * other.re + self.im * other.im) / norm,
+            (self.im * other.re - self.re * other.im) / norm,
+        )
+    }
+}
+
+/// Reflectance, averaged over both polarizations, of light arriving with
+/// angle cosine `cos_theta` from void to a film of refraction index `film`
+/// and `thickness` over a `substrate`. Both lengths in nanometers.
+fn airy_reflectance(
+    cos_theta: f64,
+    film: f64,
+    thickness: f64,
+    substrate: Complex,
+    wavelength: f64,
+) -> f64 {
+    let one = Complex::real(1.0);
+    let n1 = one;
+    let n2 = Complex::real(film);
+    let n3 = substrate;
+    let cos1 = Complex::real(cos_theta);
+    let sin2 = Complex::real(1.0 - cos_theta * cos_theta);
+    // Snell's law gives the cosines inside the film and the substrate
+    let cos2 = (one - sin2 / (n2 * n2)).sqrt();
+    let cos3 = (one - sin2 / (n3 * n3)).sqrt();
+
+    let r12_s = (n1 * cos1 - n2 * cos2) / (n1 * cos1 + n2 * cos2);
+    let r12_p = (n2 * cos1 - n1 * cos2) / (n2 * cos1 + n1 * cos2);
+    let r23_s = (n2 * cos2 - n3 * cos3) / (n2 * cos2 + n3 * cos3);
+    let r23_p = (n3 * cos2 - n2 * cos3) / (n3 * cos2 + n2 * cos3);
+
+    // Phase difference between the light reflected at each side of the film
+    let phase = Complex::real(4.0 * PI * thickness / wavelength) * n2 * cos2;
+    // The film may absorb if the light is evanescent in it
+    let shift = Complex::from_phase(phase.re) * Complex::real((-phase.im).exp());
+
+    let r = |r12: Complex, r23: Complex| (r12 + r23 * shift) / (one + r12 * r23 * shift);
+    0.5 * (r(r12_s, r23_s).norm_squared() + r(r12_p, r23_p).norm_squared())
+}
+
+/// Wavelengths and the RGB color of each of them, normalized so a flat
+/// reflectance is white
+fn film_wavelengths() -> &'static [(f64, Vec3); FILM_WAVELENGTHS] {
+    static WAVELENGTHS: OnceLock<[(f64, Vec3); FILM_WAVELENGTHS]> = OnceLock::new();
+    WAVELENGTHS.get_or_init(|| {
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / FILM_WAVELENGTHS as f64;
+        let mut wavelengths = [(0.0, Vec3::zero()); FILM_WAVELENGTHS];
+        for (i, sample) in wavelengths.iter_mut().enumerate() {
+            let wavelength = LAMBDA_MIN + (i as f64 + 0.5) * step;
+            *sample = (wavelength, xyz_to_rgb(&cie_xyz(wavelength)));
+        }
+        let white: Vec3 = wavelengths.iter().map(|(_, rgb)| *rgb).sum();
+        for (_, rgb) in wavelengths.iter_mut() {
+            *rgb = rgb.zip_with(white, |c, w| c / w);
+        }
+        wavelengths
+    })
+}
+
+/// Thin transparent film over the surface of a material
+///
+/// Light reflected at the top and at the bottom of the film interferes,
+/// which tints the reflections with colors that change with the angle and
+/// the thickness: soap bubbles, oil slicks or coated lenses. Films of a few
+/// hundred nanometers show the strongest colors.
+#[derive(Clone)]
+pub struct ThinFilm {
+    /// Texture with the thickness of the film in nanometers. Only its first
+    /// channel is used.
+    pub thickness: Arc<dyn Texture>,
+    /// Refraction index of the film
+    pub index_refraction: f64,
+}
+
+impl ThinFilm {
+    /// Creates a film with the same `thickness` (in nanometers) everywhere
+    pub fn new(thickness: f64, index_refraction: f64) -> Self {
+        Self {
+            thickness: Arc::new(SolidColor::gray(thickness)),
+            index_refraction,
+        }
+    }
+
+    /// Reflectance of the film at a hit over a substrate with refraction
+    /// index `eta + i k` (per color channel), for light arriving from void
+    /// with angle cosine `cos_theta`. At a single `wavelength` the result
+    /// is gray, else it is the color of the reflectance over the spectrum.
+    pub fn reflectance(
+        &self,
+        cos_theta: f64,
+        eta: &Vec3,
+        k: &Vec3,
+        wavelength: Option<f64>,
+        hit: &HitRecord,
+    ) -> Vec3 {
+        let cos_theta = cos_theta.clamp_(0.0, 1.0);
+        let thickness = self.thickness.value_at(hit).x().max(0.0);
+        let at = |wavelength: f64| {
+            let substrate = Complex::new(
+                rgb_to_spectrum(eta, wavelength),
+                rgb_to_spectrum(k, wavelength),
+            );
+            airy_reflectance(
+                cos_theta,
+                self.index_refraction,
+                thickness,
+                substrate,
+                wavelength,
+            )
+            .clamp_(0.0, 1.0)
+        };
+
+        match wavelength {
+            Some(wavelength) => Vec3::splat(at(wavelength)),
+            None => film_wavelengths()
+                .iter()
+                .map(|(wavelength, rgb)| *rgb * at(*wavelength))
+                .sum::<Vec3>()
+                .map(|c| c.clamp_(0.0, 1.0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn film_without_thickness() {
+        // A film of no thickness is the bare interface
+        for cos_theta in [1.0, 0.7, 0.2] {
+            let dielectric = airy_reflectance(cos_theta, 1.33, 0.0, Complex::real(1.5), 550.0);
+            assert!((dielectric - fresnel_dielectric(cos_theta, 1.5)).abs() < 1e-9);
+
+            let conductor = airy_reflectance(cos_theta, 1.33, 0.0, Complex::new(0.2, 3.9), 550.0);
+            let expected = fresnel_conductor(cos_theta, &Vec3::splat(0.2), &Vec3::splat(3.9));
+            assert!((conductor - expected.x()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn quarter_wave_coating() {
+        // Anti-reflective coating: a quarter wave film with the geometric
+        // mean of the indices cancels the reflection at normal incidence
+        let film = 1.5f64.sqrt();
+        let thickness = 550.0 / (4.0 * film);
+        let r = airy_reflectance(1.0, film, thickness, Complex::real(1.5), 550.0);
+        assert!(r < 1e-9);
+    }
+}
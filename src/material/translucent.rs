@@ -0,0 +1,106 @@
+use super::*;
+use std::f64::consts::FRAC_1_PI;
+
+/// Translucent thin sheet
+///
+/// Diffuse surface that also lets light through, scattering it diffusely
+/// at the other side, like paper, leaves or a lampshade. The sheet has no
+/// thickness, so it is meant for single sided geometry.
+#[derive(Clone)]
+pub struct Translucent<R: Texture, T: Texture> {
+    /// Texture with the light reflected diffusely at the side of the ray
+    pub reflectance: R,
+    /// Texture with the light transmitted diffusely to the other side. The
+    /// sum with the reflectance should not go over `1.0`.
+    pub transmittance: T,
+}
+
+impl Translucent<SolidColor, SolidColor> {
+    /// Constructs a [Translucent] material with [SolidColor] textures
+    pub fn from_colors(reflectance: Vec3, transmittance: Vec3) -> Self {
+        Self {
+            reflectance: SolidColor { color: reflectance },
+            transmittance: SolidColor {
+                color: transmittance,
+            },
+        }
+    }
+}
+
+impl<R: Texture, T: Texture> Translucent<R, T> {
+    /// Returns the reflectance, transmittance and the probability of
+    /// sampling a reflection at the hit
+    fn lobes(&self, hit: &HitRecord) -> (Vec3, Vec3, f64) {
+        let reflectance = self.reflectance.value_at(hit);
+        let transmittance = self.transmittance.value_at(hit);
+        let (r, t) = (reflectance.luminance(), transmittance.luminance());
+        let probability = if r + t > 0.0 { r / (r + t) } else { 1.0 };
+        (reflectance, transmittance, probability)
+    }
+}
+
+impl<R: Texture, T: Texture> Material for Translucent<R, T> {
+    fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
+        let (reflectance, transmittance, probability) = self.lobes(hit);
+        let (side, attenuation) = if probability > rand::random() {
+            (hit.normal, reflectance / probability)
+        } else {
+            (-hit.normal, transmittance / (1.0 - probability))
+        };
+
+        let direction = ONB::build_from_w(&side).local_vec(&Vec3::random_cosine_direction());
+        let scattered = Ray::new(hit.point, direction, r_in.time);
+        Some((attenuation, scattered))
+    }
+
+    fn eval(&self, _r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        let (reflectance, transmittance, _) = self.lobes(hit);
+        let cosine = hit.normal.dot(&direction.unit_vector());
+        if cosine > 0.0 {
+            reflectance * cosine * FRAC_1_PI
+        } else {
+            transmittance * -cosine * FRAC_1_PI
+        }
+    }
+
+    fn scattering_pdf(&self, _r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        let (_, _, probability) = self.lobes(hit);
+        let cosine = hit.normal.dot(&direction.unit_vector());
+        if cosine > 0.0 {
+            probability * cosine * FRAC_1_PI
+        } else {
+            (1.0 - probability) * -cosine * FRAC_1_PI
+        }
+    }
+
+    fn is_specular(&self, _hit: &HitRecord) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+
+    #[test]
+    fn reflection_and_transmission() {
+        let material = Translucent::from_colors(Vec3::splat(0.6), Vec3::splat(0.3));
+        let from = Vec3::new(1.0, 0.0, 1.0);
+        let (r, hit) = hit_from(from, &material);
+
+        // Same cosine at both sides, each one with its lobe
+        let reflected = Vec3::new(0.0, 0.0, 1.0);
+        let transmitted = Vec3::new(0.0, 0.0, -1.0);
+        assert!((material.eval(&r, &hit, &reflected).x() - 0.6 * FRAC_1_PI).abs() < 1e-12);
+        assert!((material.eval(&r, &hit, &transmitted).x() - 0.3 * FRAC_1_PI).abs() < 1e-12);
+        let pdf = material.scattering_pdf(&r, &hit, &reflected);
+        assert!((pdf - 2.0 / 3.0 * FRAC_1_PI).abs() < 1e-12);
+        let pdf = material.scattering_pdf(&r, &hit, &transmitted);
+        assert!((pdf - 1.0 / 3.0 * FRAC_1_PI).abs() < 1e-12);
+
+        assert_scatter_matches_eval(&material, from);
+        let albedo = albedo(&material, from, 1000);
+        assert!((albedo.x() - 0.9).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,87 @@
+use super::*;
+
+/// Two-sided material
+///
+/// Uses a different material for each face of a surface: the `front` one
+/// where the outward normal of the object points to, and the `back` one at
+/// the other side. Useful for single sided geometry like the pages of a book
+/// or a painted sign.
+#[derive(Clone)]
+pub struct TwoSided<F: Material, B: Material> {
+    /// Material of the face the outward normal points to
+    pub front: F,
+    /// Material of the opposite face
+    pub back: B,
+}
+
+impl<F: Material, B: Material> TwoSided<F, B> {
+    /// Returns the material of the face of the hit
+    fn side(&self, hit: &HitRecord) -> &dyn Material {
+        if hit.front_face {
+            &self.front
+        } else {
+            &self.back
+        }
+    }
+}
+
+impl<F: Material, B: Material> Material for TwoSided<F, B> {
+    fn scatter(&self, r_in: &Ray, hit: &HitRecord) -> Option<(Vec3, Ray)> {
+        self.side(hit).scatter(r_in, hit)
+    }
+
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        self.side(hit).emitted(hit)
+    }
+
+    fn eval(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> Vec3 {
+        self.side(hit).eval(r_in, hit, direction)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        self.side(hit).scattering_pdf(r_in, hit, direction)
+    }
+
+    fn is_specular(&self, hit: &HitRecord) -> bool {
+        self.side(hit).is_specular(hit)
+    }
+
+    fn is_volumetric(&self) -> bool {
+        self.front.is_volumetric() && self.back.is_volumetric()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::*;
+
+    #[test]
+    fn each_face_uses_its_material() {
+        let front = Lambertian::from_color(Vec3::new(1.0, 0.0, 0.0));
+        let back = Lambertian::from_color(Vec3::new(0.0, 0.0, 1.0));
+        let material = TwoSided {
+            front: front.clone(),
+            back: back.clone(),
+        };
+
+        let (r, hit) = hit_from(Vec3::new(0.0, 0.0, 1.0), &material);
+        assert!(hit.front_face);
+        let direction = Vec3::new(0.0, 0.0, 1.0);
+        assert_eq!(
+            material.eval(&r, &hit, &direction),
+            front.eval(&r, &hit, &direction)
+        );
+
+        let (r, hit) = hit_from(Vec3::new(0.0, 0.0, -1.0), &material);
+        assert!(!hit.front_face);
+        let direction = Vec3::new(0.0, 0.0, -1.0);
+        assert_eq!(
+            material.eval(&r, &hit, &direction),
+            back.eval(&r, &hit, &direction)
+        );
+        assert!(material.eval(&r, &hit, &direction).x() == 0.0);
+        let (attenuation, _) = material.scatter(&r, &hit).unwrap();
+        assert_eq!(attenuation, Vec3::new(0.0, 0.0, 1.0));
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use rand::Rng;
 
 const AABB_ZERO_PADDING: f64 = 0.0001;
 
@@ -23,6 +24,7 @@ pub struct Rect<A: RectAxis, M: Material> {
     pub material: M,
 }
 
+#[derive(Clone)]
 pub struct XY;
 impl RectAxis for XY {
     const AXIS: Axis = Axis::Z;
@@ -30,6 +32,7 @@ impl RectAxis for XY {
     const OTHER2: Axis = Axis::Y;
 }
 
+#[derive(Clone)]
 pub struct XZ;
 impl RectAxis for XZ {
     const AXIS: Axis = Axis::Y;
@@ -37,6 +40,7 @@ impl RectAxis for XZ {
     const OTHER2: Axis = Axis::Z;
 }
 
+#[derive(Clone)]
 pub struct YZ;
 impl RectAxis for YZ {
     const AXIS: Axis = Axis::X;
@@ -44,8 +48,19 @@ impl RectAxis for YZ {
     const OTHER2: Axis = Axis::Z;
 }
 
+impl<A: RectAxis, M: Material> Rect<A, M> {
+    /// Derivatives of the point with respect to `u` and `v`
+    fn tangents(&self) -> (Vec3, Vec3) {
+        let mut dpdu = Vec3::zero();
+        dpdu[A::OTHER1] = self.a1 - self.a0;
+        let mut dpdv = Vec3::zero();
+        dpdv[A::OTHER2] = self.b1 - self.b0;
+        (dpdu, dpdv)
+    }
+}
+
 impl<A: RectAxis, M: Material> Hittable for Rect<A, M> {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         let t = (self.k - r.origin[A::AXIS]) / r.direction[A::AXIS];
 
         if t < t_min || t > t_max {
@@ -67,15 +82,11 @@ impl<A: RectAxis, M: Material> Hittable for Rect<A, M> {
         };
         let point = r.at(t);
 
-        Some(HitRecord::new(
-            r,
-            t,
-            u,
-            v,
-            point,
-            outward_normal,
-            &self.material,
-        ))
+        let (dpdu, dpdv) = self.tangents();
+        Some(
+            HitRecord::new(r, t, u, v, point, outward_normal, &self.material)
+                .with_tangents(dpdu, dpdv),
+        )
     }
 
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
@@ -93,4 +104,56 @@ impl<A: RectAxis, M: Material> Hittable for Rect<A, M> {
             maximum: v_max,
         })
     }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        if let Some(hit) = self.hit(&Ray::new(*origin, *direction, 0.0), 0.001, f64::INFINITY) {
+            let area = (self.a1 - self.a0) * (self.b1 - self.b0);
+            let distance_squared = hit.t * hit.t * direction.length_squared();
+            let cosine = (direction.dot(&hit.normal) / direction.length()).abs();
+
+            distance_squared / (cosine * area)
+        } else {
+            0.0
+        }
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let mut rng = rand::thread_rng();
+        let mut random_point = Vec3::zero();
+        random_point[A::OTHER1] = rng.gen_range(self.a0..self.a1);
+        random_point[A::OTHER2] = rng.gen_range(self.b0..self.b1);
+        random_point[A::AXIS] = self.k;
+        random_point - *origin
+    }
+
+    fn sample_surface(&self, time: f64) -> Option<(HitRecord<'_>, f64)> {
+        let mut rng = rand::thread_rng();
+        let u: f64 = rng.gen();
+        let v: f64 = rng.gen();
+        let mut point = Vec3::zero();
+        point[A::OTHER1] = self.a0 + u * (self.a1 - self.a0);
+        point[A::OTHER2] = self.b0 + v * (self.b1 - self.b0);
+        point[A::AXIS] = self.k;
+        let outward_normal = {
+            let mut v = Vec3::zero();
+            v[A::AXIS] = 1.0;
+            v
+        };
+        let r = Ray::new(point + outward_normal, -outward_normal, time);
+        let (dpdu, dpdv) = self.tangents();
+        let record = HitRecord::new(&r, 1.0, u, v, point, outward_normal, &self.material)
+            .with_tangents(dpdu, dpdv);
+        let area = (self.a1 - self.a0) * (self.b1 - self.b0);
+        Some((record, 1.0 / area))
+    }
+
+    fn surface_pdf(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        if self
+            .hit(&Ray::new(*origin, *direction, 0.0), 0.001, f64::INFINITY)
+            .is_none()
+        {
+            return 0.0;
+        }
+        1.0 / ((self.a1 - self.a0) * (self.b1 - self.b0))
+    }
 }
@@ -0,0 +1,107 @@
+use super::*;
+use crate::texture::Texture;
+
+/// How the opacity of an [AlphaMask] decides if a hit is kept
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaMode {
+    /// The surface is solid where the opacity is at least the given value and
+    /// fully transparent elsewhere. Gives crisp edges, like leaves or fences.
+    Threshold(f64),
+    /// A hit is kept with probability equal to the opacity, so half opaque
+    /// surfaces let half the rays through. Noisier, but shows soft edges.
+    Stochastic,
+}
+
+/// Cut-out object
+///
+/// Hides the parts of an object where its opacity texture is transparent,
+/// so rays (shadow rays included) pass straight through them. The opacity
+/// is given by the [alpha](Texture::alpha) of the texture, like the alpha
+/// channel of an [ImageTexture](crate::texture::ImageTexture).
+#[derive(Clone)]
+pub struct AlphaMask<H: Hittable, T: Texture> {
+    pub object: H,
+    /// Texture with the opacity [`0.0`, `1.0`] of the surface
+    pub opacity: T,
+    pub mode: AlphaMode,
+}
+
+impl<H: Hittable, T: Texture> AlphaMask<H, T> {
+    /// Creates a new cut-out object, transparent where the opacity is below `0.5`
+    pub fn new(object: H, opacity: T) -> Self {
+        Self {
+            object,
+            opacity,
+            mode: AlphaMode::Threshold(0.5),
+        }
+    }
+
+    /// Whether the surface is solid at the given hit
+    fn is_opaque(&self, hit: &HitRecord) -> bool {
+        let alpha = self.opacity.alpha(hit.u, hit.v, &hit.point);
+        match self.mode {
+            AlphaMode::Threshold(threshold) => alpha >= threshold,
+            AlphaMode::Stochastic => alpha > rand::random(),
+        }
+    }
+}
+
+impl<H: Hittable, T: Texture> Hittable for AlphaMask<H, T> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let mut t_min = t_min;
+        loop {
+            let hit = self.object.hit(r, t_min, t_max)?;
+            if self.is_opaque(&hit) {
+                return Some(hit);
+            }
+            // Keep looking behind the transparent hit
+            t_min = hit.t + f64::EPSILON.max(hit.t.abs() * 1e-9);
+        }
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        self.object.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::texture::SolidColor;
+
+    /// Texture with a constant opacity
+    struct Opacity(f64);
+
+    impl Texture for Opacity {
+        fn value(&self, _u: f64, _v: f64, _p: &Vec3) -> Vec3 {
+            Vec3::new(1.0, 1.0, 1.0)
+        }
+
+        fn alpha(&self, _u: f64, _v: f64, _p: &Vec3) -> f64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn threshold() {
+        let sphere = |opacity| {
+            AlphaMask::new(
+                Sphere {
+                    center: Vec3::new(0.0, 0.0, 0.0),
+                    radius: 1.0,
+                    material: Lambertian {
+                        albedo: SolidColor::gray(0.5),
+                    },
+                },
+                Opacity(opacity),
+            )
+        };
+        let r = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+
+        assert!(sphere(0.2).hit(&r, 0.001, f64::INFINITY).is_none());
+        let opaque = sphere(0.8);
+        let hit = opaque.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::sync::Arc;
 
 /// Block... or Box :)
 pub struct Block {
@@ -8,65 +9,66 @@ pub struct Block {
 }
 
 impl Block {
-    pub fn new<M: 'static + Material + Clone>(p0: Vec3, p1: Vec3, material: M) -> Self {
-        let mut side_rectangles: Vec<Box<dyn Hittable>> = Vec::new();
-
-        side_rectangles.push(Box::new(Rect {
-            in_plane: XY,
-            a0: p0.x(),
-            a1: p1.x(),
-            b0: p0.y(),
-            b1: p1.y(),
-            k: p1.z(),
-            material: material.clone(),
-        }));
-        side_rectangles.push(Box::new(Rect {
-            in_plane: XY,
-            a0: p0.x(),
-            a1: p1.x(),
-            b0: p0.y(),
-            b1: p1.y(),
-            k: p0.z(),
-            material: material.clone(),
-        }));
-
-        side_rectangles.push(Box::new(Rect {
-            in_plane: XZ,
-            a0: p0.x(),
-            a1: p1.x(),
-            b0: p0.z(),
-            b1: p1.z(),
-            k: p1.y(),
-            material: material.clone(),
-        }));
-        side_rectangles.push(Box::new(Rect {
-            in_plane: XZ,
-            a0: p0.x(),
-            a1: p1.x(),
-            b0: p0.z(),
-            b1: p1.z(),
-            k: p0.y(),
-            material: material.clone(),
-        }));
-
-        side_rectangles.push(Box::new(Rect {
-            in_plane: YZ,
-            a0: p0.y(),
-            a1: p1.y(),
-            b0: p0.z(),
-            b1: p1.z(),
-            k: p1.x(),
-            material: material.clone(),
-        }));
-        side_rectangles.push(Box::new(Rect {
-            in_plane: YZ,
-            a0: p0.y(),
-            a1: p1.y(),
-            b0: p0.z(),
-            b1: p1.z(),
-            k: p0.x(),
-            material,
-        }));
+    /// Creates a new [Block] between the corners `p0` and `p1`. The 6 sides
+    /// share the material.
+    pub fn new<M: 'static + Material>(p0: Vec3, p1: Vec3, material: M) -> Self {
+        let material = Arc::new(material);
+        let side_rectangles: Vec<Box<dyn Hittable>> = vec![
+            Box::new(Rect {
+                in_plane: XY,
+                a0: p0.x(),
+                a1: p1.x(),
+                b0: p0.y(),
+                b1: p1.y(),
+                k: p1.z(),
+                material: material.clone(),
+            }),
+            Box::new(Rect {
+                in_plane: XY,
+                a0: p0.x(),
+                a1: p1.x(),
+                b0: p0.y(),
+                b1: p1.y(),
+                k: p0.z(),
+                material: material.clone(),
+            }),
+            Box::new(Rect {
+                in_plane: XZ,
+                a0: p0.x(),
+                a1: p1.x(),
+                b0: p0.z(),
+                b1: p1.z(),
+                k: p1.y(),
+                material: material.clone(),
+            }),
+            Box::new(Rect {
+                in_plane: XZ,
+                a0: p0.x(),
+                a1: p1.x(),
+                b0: p0.z(),
+                b1: p1.z(),
+                k: p0.y(),
+                material: material.clone(),
+            }),
+            Box::new(Rect {
+                in_plane: YZ,
+                a0: p0.y(),
+                a1: p1.y(),
+                b0: p0.z(),
+                b1: p1.z(),
+                k: p1.x(),
+                material: material.clone(),
+            }),
+            Box::new(Rect {
+                in_plane: YZ,
+                a0: p0.y(),
+                a1: p1.y(),
+                b0: p0.z(),
+                b1: p1.z(),
+                k: p0.x(),
+                material,
+            }),
+        ];
 
         Self {
             p0,
@@ -79,7 +81,7 @@ impl Block {
 }
 
 impl Hittable for Block {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         self.sides.hit(r, t_min, t_max)
     }
 
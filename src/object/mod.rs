@@ -8,6 +8,7 @@
 use crate::aabb::AABB;
 use crate::hittable::{HitRecord, Hittable, HittableList};
 use crate::material::Material;
+use crate::onb::ONB;
 use crate::ray::Ray;
 use vec3::{Axis, Vec3};
 
@@ -23,3 +24,5 @@ mod rotate;
 pub use rotate::*;
 mod translate;
 pub use translate::*;
+mod alpha_mask;
+pub use alpha_mask::*;
@@ -22,7 +22,7 @@ impl<M: Material + Clone> MovingSphere<M> {
 }
 
 impl<M: Material + Clone> Hittable for MovingSphere<M> {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         let center_now = self.center(r.time);
         let oc = r.origin - center_now;
         let a = r.direction.length_squared();
@@ -50,7 +50,9 @@ impl<M: Material + Clone> Hittable for MovingSphere<M> {
         let hit_point = r.at(root);
         let outward_normal = (hit_point - center_now) / self.radius;
         let (u, v) = super::sphere::get_sphere_uv(&outward_normal);
-        let record = HitRecord::new(&r, root, u, v, hit_point, outward_normal, &self.material);
+        let (dpdu, dpdv) = super::sphere::get_sphere_tangents(&outward_normal, self.radius);
+        let record = HitRecord::new(r, root, u, v, hit_point, outward_normal, &self.material)
+            .with_tangents(dpdu, dpdv);
 
         Some(record)
     }
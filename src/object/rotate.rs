@@ -54,10 +54,9 @@ impl<H: Hittable> RotateY<H> {
             bbox,
         }
     }
-}
 
-impl<H: Hittable> Hittable for RotateY<H> {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    /// Rotates a ray to the space of the object
+    fn object_ray(&self, r: &Ray) -> Ray {
         let mut origin = r.origin;
         origin[0] = self.cos_theta * r.origin[0] - self.sin_theta * r.origin[2];
         origin[2] = self.sin_theta * r.origin[0] + self.cos_theta * r.origin[2];
@@ -66,19 +65,35 @@ impl<H: Hittable> Hittable for RotateY<H> {
         direction[0] = self.cos_theta * r.direction[0] - self.sin_theta * r.direction[2];
         direction[2] = self.sin_theta * r.direction[0] + self.cos_theta * r.direction[2];
 
-        let rotated_r = Ray::new(origin, direction, r.time);
+        Ray {
+            origin,
+            direction,
+            ..*r
+        }
+    }
+}
+
+impl<H: Hittable> Hittable for RotateY<H> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let rotated_r = self.object_ray(r);
 
         if let Some(mut hit) = self.object.hit(&rotated_r, t_min, t_max) {
             let mut point = hit.point;
             point[0] = self.cos_theta * hit.point[0] + self.sin_theta * hit.point[2];
             point[2] = -self.sin_theta * hit.point[0] + self.cos_theta * hit.point[2];
 
-            let mut normal = hit.normal;
-            normal[0] = self.cos_theta * hit.normal[0] + self.sin_theta * hit.normal[2];
-            normal[2] = -self.sin_theta * hit.normal[0] + self.cos_theta * hit.normal[2];
+            let rotate = |v: Vec3| {
+                let mut rotated = v;
+                rotated[0] = self.cos_theta * v[0] + self.sin_theta * v[2];
+                rotated[2] = -self.sin_theta * v[0] + self.cos_theta * v[2];
+                rotated
+            };
 
             hit.point = point;
-            hit.normal = normal;
+            hit.normal = rotate(hit.normal);
+            hit.geometric_normal = rotate(hit.geometric_normal);
+            hit.dpdu = rotate(hit.dpdu);
+            hit.dpdv = rotate(hit.dpdv);
 
             Some(hit)
         } else {
@@ -89,4 +104,8 @@ impl<H: Hittable> Hittable for RotateY<H> {
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
         self.bbox.clone()
     }
+
+    fn node_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> u64 {
+        self.object.node_visits(&self.object_ray(r), t_min, t_max)
+    }
 }
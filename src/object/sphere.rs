@@ -23,8 +23,45 @@ pub fn get_sphere_uv(p: &Vec3) -> (f64, f64) {
     (u, v)
 }
 
+/// Calculates the derivatives of the point of a sphere of radius `radius`
+/// with respect to the coords given by [get_sphere_uv]
+///
+/// The `p` parameter is a normal to the sphere surface
+#[inline]
+pub fn get_sphere_tangents(p: &Vec3, radius: f64) -> (Vec3, Vec3) {
+    use std::f64::consts::{PI, TAU};
+    let (x, y, z) = (p.x(), p.y(), p.z());
+    let sin_theta = (x * x + z * z).sqrt();
+    let dpdu = TAU * radius * Vec3::new(z, 0.0, -x);
+    // u is undefined at the poles
+    if sin_theta < 1e-8 {
+        return (
+            Vec3::new(TAU * radius, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, PI * radius),
+        );
+    }
+    let dpdv = PI * radius * Vec3::new(-x * y / sin_theta, sin_theta, -y * z / sin_theta);
+    (dpdu, dpdv)
+}
+
+/// Generates a random direction towards a sphere of radius `radius` whose
+/// center is at a squared distance of `distance_squared`. The direction is
+/// given in a local space where the sphere lies in the `z` axis.
+fn random_to_sphere(radius: f64, distance_squared: f64) -> Vec3 {
+    use std::f64::consts::TAU;
+    let r1: f64 = rand::random();
+    let r2: f64 = rand::random();
+    let z = 1.0 + r2 * ((1.0 - radius * radius / distance_squared).sqrt() - 1.0);
+
+    let phi = TAU * r1;
+    let x = phi.cos() * (1.0 - z * z).sqrt();
+    let y = phi.sin() * (1.0 - z * z).sqrt();
+
+    Vec3::new(x, y, z)
+}
+
 impl<M: Material + Clone> Hittable for Sphere<M> {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         let oc = r.origin - self.center;
         let a = r.direction.length_squared();
         let half_b = oc.dot(&r.direction);
@@ -51,7 +88,9 @@ impl<M: Material + Clone> Hittable for Sphere<M> {
         let hit_point = r.at(root);
         let outward_normal = (hit_point - self.center) / self.radius;
         let (u, v) = get_sphere_uv(&outward_normal);
-        let record = HitRecord::new(&r, root, u, v, hit_point, outward_normal, &self.material);
+        let (dpdu, dpdv) = get_sphere_tangents(&outward_normal, self.radius);
+        let record = HitRecord::new(r, root, u, v, hit_point, outward_normal, &self.material)
+            .with_tangents(dpdu, dpdv);
 
         Some(record)
     }
@@ -63,4 +102,49 @@ impl<M: Material + Clone> Hittable for Sphere<M> {
             maximum: self.center + Vec3::splat(self.radius),
         })
     }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        if self
+            .hit(&Ray::new(*origin, *direction, 0.0), 0.001, f64::INFINITY)
+            .is_none()
+        {
+            return 0.0;
+        }
+
+        let cos_theta_max =
+            (1.0 - self.radius * self.radius / (self.center - *origin).length_squared()).sqrt();
+        let solid_angle = std::f64::consts::TAU * (1.0 - cos_theta_max);
+
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let direction = self.center - *origin;
+        let distance_squared = direction.length_squared();
+        let uvw = ONB::build_from_w(&direction);
+        uvw.local_vec(&random_to_sphere(self.radius, distance_squared))
+    }
+
+    fn sample_surface(&self, time: f64) -> Option<(HitRecord<'_>, f64)> {
+        let outward_normal = Vec3::random_unit_vector();
+        let point = self.center + self.radius * outward_normal;
+        let (u, v) = get_sphere_uv(&outward_normal);
+        let r = Ray::new(point + outward_normal, -outward_normal, time);
+        let (dpdu, dpdv) = get_sphere_tangents(&outward_normal, self.radius);
+        let record = HitRecord::new(&r, 1.0, u, v, point, outward_normal, &self.material)
+            .with_tangents(dpdu, dpdv);
+        let area = 2.0 * std::f64::consts::TAU * self.radius * self.radius;
+        Some((record, 1.0 / area))
+    }
+
+    fn surface_pdf(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        if self
+            .hit(&Ray::new(*origin, *direction, 0.0), 0.001, f64::INFINITY)
+            .is_none()
+        {
+            return 0.0;
+        }
+        let area = 2.0 * std::f64::consts::TAU * self.radius * self.radius;
+        1.0 / area
+    }
 }
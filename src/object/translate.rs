@@ -14,8 +14,11 @@ impl<H: Hittable> Translate<H> {
 }
 
 impl<H: Hittable> Hittable for Translate<H> {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let moved_r = Ray::new(r.origin - self.offset, r.direction, r.time);
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let moved_r = Ray {
+            origin: r.origin - self.offset,
+            ..*r
+        };
 
         if let Some(mut hit) = self.object.hit(&moved_r, t_min, t_max) {
             hit.point += self.offset;
@@ -35,4 +38,30 @@ impl<H: Hittable> Hittable for Translate<H> {
             None
         }
     }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        self.object.pdf_value(&(*origin - self.offset), direction)
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        self.object.random(&(*origin - self.offset))
+    }
+
+    fn sample_surface(&self, time: f64) -> Option<(HitRecord<'_>, f64)> {
+        let (mut hit, pdf) = self.object.sample_surface(time)?;
+        hit.point += self.offset;
+        Some((hit, pdf))
+    }
+
+    fn surface_pdf(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        self.object.surface_pdf(&(*origin - self.offset), direction)
+    }
+
+    fn node_visits(&self, r: &Ray, t_min: f64, t_max: f64) -> u64 {
+        let moved_r = Ray {
+            origin: r.origin - self.offset,
+            ..*r
+        };
+        self.object.node_visits(&moved_r, t_min, t_max)
+    }
 }
@@ -0,0 +1,57 @@
+use vec3::Vec3;
+
+/// Orthonormal basis
+///
+/// Used to transform vectors between a local space where `w` is the "up"
+/// axis and world space.
+pub struct ONB {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl ONB {
+    /// Builds an [ONB] where the [w](ONB::w) axis points in the direction of `n`
+    pub fn build_from_w(n: &Vec3) -> Self {
+        let w = n.unit_vector();
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(&a).unit_vector();
+        let u = w.cross(&v);
+        Self { u, v, w }
+    }
+
+    /// Builds an [ONB] where the [w](ONB::w) axis points in the direction of `n`
+    /// and the [u](ONB::u) axis follows the tangent `t` as much as possible
+    pub fn build_from_wu(n: &Vec3, t: &Vec3) -> Self {
+        let w = n.unit_vector();
+        let u = *t - w.dot(t) * w;
+        if u.near_zero() {
+            return Self::build_from_w(n);
+        }
+        let u = u.unit_vector();
+        let v = w.cross(&u);
+        Self { u, v, w }
+    }
+
+    /// Transforms the local coords (`a`, `b`, `c`) into world space
+    #[inline]
+    pub fn local(&self, a: f64, b: f64, c: f64) -> Vec3 {
+        a * self.u + b * self.v + c * self.w
+    }
+
+    /// Transforms the local vector `a` into world space
+    #[inline]
+    pub fn local_vec(&self, a: &Vec3) -> Vec3 {
+        self.local(a.x(), a.y(), a.z())
+    }
+
+    /// Transforms the world space vector `a` into local coords
+    #[inline]
+    pub fn to_local(&self, a: &Vec3) -> Vec3 {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+}
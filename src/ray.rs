@@ -6,6 +6,13 @@ pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
     pub time: f64,
+    /// Wavelength in nanometers carried by the Ray when rendering in
+    /// spectral mode. `None` for regular RGB rendering.
+    pub wavelength: Option<f64>,
+    /// Angle (in radians) by which the Ray widens like a cone, so it covers
+    /// a footprint of the scene instead of a point. Camera rays spread over
+    /// their pixel and the rest have `0.0`.
+    pub spread: f64,
 }
 
 impl Ray {
@@ -15,7 +22,7 @@ impl Ray {
     ///
     /// * `origin` - A Vec3 representing the point where the Ray started.
     /// * `direction` - A Vec3 representing the Ray direction. Usually a
-    ///     unit vector but it is not enforced.
+    ///   unit vector but it is not enforced.
     ///
     /// Keep in mind that the Ray takes the ownership of the Vec3s when it is created.
     pub fn new(origin: Vec3, direction: Vec3, time: f64) -> Self {
@@ -23,6 +30,8 @@ impl Ray {
             origin,
             direction,
             time,
+            wavelength: None,
+            spread: 0.0,
         }
     }
 
@@ -47,7 +56,9 @@ mod tests {
             Ray {
                 origin,
                 direction,
-                time
+                time,
+                wavelength: None,
+                spread: 0.0,
             }
         );
     }
@@ -61,6 +72,8 @@ mod tests {
             origin,
             direction,
             time,
+            wavelength: None,
+            spread: 0.0,
         };
         assert_eq!(ray.at(14.0), Vec3::new(15.0, 1.0, 1.0));
     }
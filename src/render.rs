@@ -1,18 +1,28 @@
 use crate::camera::Camera;
+use crate::film::Film;
 use crate::hittable::Hittable;
-use crate::image_helper::Image;
+use crate::image_helper::{par_compute_pixels, Image};
+use crate::integrator::{Integrator, SceneContext};
+use crate::light::{EnvironmentLight, Light};
 use crate::ray::Ray;
 use image::RgbImage;
 use rand::prelude::*;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc};
 use std::time::{Duration, Instant};
 use vec3::Vec3;
 
 /// Configuration of a render call
 pub struct RenderConfig {
     pub world: Box<dyn Hittable>,
+    /// Objects of the world that emit light, used to sample them explicitly
+    pub lights: Box<dyn Hittable>,
+    /// Lights that are not objects of the world
+    pub delta_lights: Vec<Box<dyn Light>>,
     pub camera: Camera,
-    pub background_color: Vec3,
+    /// Light transport algorithm used
+    pub integrator: Box<dyn Integrator>,
+    /// Light that arrives when nothing is hit
+    pub environment: EnvironmentLight,
     pub image_width: usize,
     pub image_height: usize,
     pub samples_per_pixel: usize,
@@ -29,9 +39,12 @@ pub fn render(
         image_height: height,
         samples_per_pixel: spp,
         world,
+        lights,
+        delta_lights,
         max_bounces,
         camera,
-        background_color: background,
+        mut integrator,
+        environment,
         print_debug,
         threads,
     }: RenderConfig,
@@ -56,50 +69,61 @@ pub fn render(
         pb.finish();
     });
 
+    let film = Arc::new(Film::new(width, height));
+    let thread_film = Arc::clone(&film);
+
     let start_instant = Instant::now();
+    integrator.preprocess(
+        &SceneContext {
+            world: world.as_ref(),
+            lights: lights.as_ref(),
+            delta_lights: &delta_lights,
+            camera: &camera,
+            film: film.as_ref(),
+            environment: &environment,
+            max_bounces,
+        },
+        threads,
+        print_debug,
+    );
+
+    let spread = camera.pixel_spread(height);
     // gives ownership of tx, therefore when function ends, tx is disconnected
-    let image = RgbImage::par_compute(width, height, threads, tx, move |i, j| {
+    let mut pixels = par_compute_pixels(width, height, threads, tx, move |i, j| {
+        let scene = SceneContext {
+            world: world.as_ref(),
+            lights: lights.as_ref(),
+            delta_lights: &delta_lights,
+            camera: &camera,
+            film: thread_film.as_ref(),
+            environment: &environment,
+            max_bounces,
+        };
         let pixel: Vec3 = (0..spp)
             .map(|_| {
                 let mut rng = thread_rng();
                 let u = (i as f64 + rng.gen::<f64>()) / width as f64;
                 let v = (j as f64 + rng.gen::<f64>()) / height as f64;
-                let r = camera.get_ray(u, v);
-                ray_color(r, &background, world.as_ref(), max_bounces)
+                let r = Ray {
+                    spread,
+                    ..camera.get_ray(u, v)
+                };
+                integrator.radiance(&r, &scene)
             })
             .sum();
         pixel / spp as f64
     });
+
+    // Add the light that integrators splatted into any pixel
+    for (index, pixel) in pixels.iter_mut().enumerate() {
+        let i = index % width;
+        let j = height - 1 - index / width;
+        *pixel += film.get(i, j) / spp as f64;
+    }
+    let image = RgbImage::from_pixels(width, height, &pixels);
     let elapsed = start_instant.elapsed();
 
     progress_thread.join().expect("Progress thread panicked");
 
     (image, elapsed)
 }
-
-fn ray_color(r: Ray, background: &Vec3, world: &dyn Hittable, depth: u32) -> Vec3 {
-    // If maximum number of rays
-    if depth == 0 {
-        return Vec3::zero();
-    }
-
-    // If hit with some object. The min hit distance is not 0 because
-    // of course float precission. Not every ray will match exactly with 0.0
-    if let Some(hit) = world.hit(&r, 0.001, f64::INFINITY) {
-        // if hits something
-
-        // Calculate the light emitted
-        let emitted = hit.material.emitted(hit.u, hit.v, &hit.point);
-
-        if let Some((attenuation, scattered)) = hit.material.scatter(&r, &hit) {
-            // if material scatters
-            emitted + attenuation * ray_color(scattered, background, world, depth - 1)
-        } else {
-            // if it not, only emits
-            emitted
-        }
-    } else {
-        // if hits nothing, the background is visible
-        *background
-    }
-}
@@ -1,6 +1,7 @@
 use crate::camera::CameraConfig;
 use crate::constant_medium::ConstantMedium;
 use crate::hittable::{Hittable, HittableList};
+use crate::light::{EnvironmentLight, Light};
 use crate::material::*;
 use crate::object::*;
 use crate::texture::*;
@@ -10,8 +11,13 @@ use rand::Rng;
 
 pub struct Scene {
     pub world: HittableList,
+    /// Copies of the objects of [world](Scene::world) that emit light
+    pub lights: HittableList,
+    /// Lights that are not objects, like point lights
+    pub delta_lights: Vec<Box<dyn Light>>,
     pub camera_config: CameraConfig,
-    pub background_color: Vec3,
+    /// Light that arrives when nothing is hit, like the sky
+    pub environment: EnvironmentLight,
 }
 
 pub fn get_scenes() -> [&'static str; 11] {
@@ -96,16 +102,14 @@ fn random_spheres() -> Scene {
                     objects.push(Box::new(Sphere {
                         center,
                         radius: 0.2,
-                        material: Metal { albedo, fuzz },
+                        material: Metal::from_color(albedo, fuzz),
                     }));
                 } else {
                     // glass
                     objects.push(Box::new(Sphere {
                         center,
                         radius: 0.2,
-                        material: Dielectric {
-                            index_refraction: 1.5,
-                        },
+                        material: Dielectric::new(1.5),
                     }));
                 }
             }
@@ -116,9 +120,7 @@ fn random_spheres() -> Scene {
     objects.push(Box::new(Sphere {
         center: Vec3::new(0.0, 1.0, 0.0),
         radius: 1.0,
-        material: Dielectric {
-            index_refraction: 1.5,
-        },
+        material: Dielectric::new(1.5),
     }));
 
     // Big diffuse boi
@@ -132,16 +134,17 @@ fn random_spheres() -> Scene {
     objects.push(Box::new(Sphere {
         center: Vec3::new(4.0, 1.0, 0.0),
         radius: 1.0,
-        material: Metal {
-            albedo: Vec3::new(0.7, 0.6, 0.5),
-            fuzz: 0.0,
-        },
+        material: Metal::from_color(Vec3::new(0.7, 0.6, 0.5), 0.0),
     }));
 
     Scene {
         world: HittableList { objects },
+        lights: HittableList {
+            objects: Vec::new(),
+        },
+        delta_lights: Vec::new(),
         camera_config: default_cam(),
-        background_color: Vec3::new(0.7, 0.8, 1.0),
+        environment: EnvironmentLight::from_color(Vec3::new(0.7, 0.8, 1.0)),
     }
 }
 
@@ -187,16 +190,14 @@ fn random_bouncing_spheres() -> Scene {
                     objects.push(Box::new(Sphere {
                         center,
                         radius: 0.2,
-                        material: Metal { albedo, fuzz },
+                        material: Metal::from_color(albedo, fuzz),
                     }));
                 } else {
                     // glass
                     objects.push(Box::new(Sphere {
                         center,
                         radius: 0.2,
-                        material: Dielectric {
-                            index_refraction: 1.5,
-                        },
+                        material: Dielectric::new(1.5),
                     }));
                 }
             }
@@ -207,9 +208,7 @@ fn random_bouncing_spheres() -> Scene {
     objects.push(Box::new(Sphere {
         center: Vec3::new(0.0, 1.0, 0.0),
         radius: 1.0,
-        material: Dielectric {
-            index_refraction: 1.5,
-        },
+        material: Dielectric::new(1.5),
     }));
 
     // Big diffuse boi
@@ -223,16 +222,17 @@ fn random_bouncing_spheres() -> Scene {
     objects.push(Box::new(Sphere {
         center: Vec3::new(4.0, 1.0, 0.0),
         radius: 1.0,
-        material: Metal {
-            albedo: Vec3::new(0.7, 0.6, 0.5),
-            fuzz: 0.0,
-        },
+        material: Metal::from_color(Vec3::new(0.7, 0.6, 0.5), 0.0),
     }));
 
     Scene {
         world: HittableList { objects },
+        lights: HittableList {
+            objects: Vec::new(),
+        },
+        delta_lights: Vec::new(),
         camera_config: default_cam(),
-        background_color: Vec3::new(0.7, 0.8, 1.0),
+        environment: EnvironmentLight::from_color(Vec3::new(0.7, 0.8, 1.0)),
     }
 }
 
@@ -274,16 +274,14 @@ fn random_spheres_checker() -> Scene {
                     objects.push(Box::new(Sphere {
                         center,
                         radius: 0.2,
-                        material: Metal { albedo, fuzz },
+                        material: Metal::from_color(albedo, fuzz),
                     }));
                 } else {
                     // glass
                     objects.push(Box::new(Sphere {
                         center,
                         radius: 0.2,
-                        material: Dielectric {
-                            index_refraction: 1.5,
-                        },
+                        material: Dielectric::new(1.5),
                     }));
                 }
             }
@@ -294,9 +292,7 @@ fn random_spheres_checker() -> Scene {
     objects.push(Box::new(Sphere {
         center: Vec3::new(0.0, 1.0, 0.0),
         radius: 1.0,
-        material: Dielectric {
-            index_refraction: 1.5,
-        },
+        material: Dielectric::new(1.5),
     }));
 
     // Big diffuse boi
@@ -310,16 +306,17 @@ fn random_spheres_checker() -> Scene {
     objects.push(Box::new(Sphere {
         center: Vec3::new(4.0, 1.0, 0.0),
         radius: 1.0,
-        material: Metal {
-            albedo: Vec3::new(0.7, 0.6, 0.5),
-            fuzz: 0.0,
-        },
+        material: Metal::from_color(Vec3::new(0.7, 0.6, 0.5), 0.0),
     }));
 
     Scene {
         world: HittableList { objects },
+        lights: HittableList {
+            objects: Vec::new(),
+        },
+        delta_lights: Vec::new(),
         camera_config: default_cam(),
-        background_color: Vec3::new(0.7, 0.8, 1.0),
+        environment: EnvironmentLight::from_color(Vec3::new(0.7, 0.8, 1.0)),
     }
 }
 
@@ -348,8 +345,12 @@ fn checker_spheres() -> Scene {
 
     Scene {
         world: HittableList { objects },
+        lights: HittableList {
+            objects: Vec::new(),
+        },
+        delta_lights: Vec::new(),
         camera_config: aperture_0(),
-        background_color: Vec3::new(0.7, 0.8, 1.0),
+        environment: EnvironmentLight::from_color(Vec3::new(0.7, 0.8, 1.0)),
     }
 }
 
@@ -373,8 +374,12 @@ fn perlin_spheres() -> Scene {
 
     Scene {
         world: HittableList { objects },
+        lights: HittableList {
+            objects: Vec::new(),
+        },
+        delta_lights: Vec::new(),
         camera_config: aperture_0(),
-        background_color: Vec3::new(0.7, 0.8, 1.0),
+        environment: EnvironmentLight::from_color(Vec3::new(0.7, 0.8, 1.0)),
     }
 }
 
@@ -393,8 +398,12 @@ fn earth() -> Scene {
         world: HittableList {
             objects: vec![globe],
         },
+        lights: HittableList {
+            objects: Vec::new(),
+        },
+        delta_lights: Vec::new(),
         camera_config: aperture_0(),
-        background_color: Vec3::new(0.7, 0.8, 1.0),
+        environment: EnvironmentLight::from_color(Vec3::new(0.7, 0.8, 1.0)),
     }
 }
 
@@ -403,8 +412,12 @@ fn black() -> Scene {
         world: HittableList {
             objects: Vec::new(),
         },
+        lights: HittableList {
+            objects: Vec::new(),
+        },
+        delta_lights: Vec::new(),
         camera_config: default_cam(),
-        background_color: Vec3::zero(),
+        environment: EnvironmentLight::from_color(Vec3::zero()),
     }
 }
 
@@ -435,7 +448,7 @@ fn simple_light() -> Scene {
     }));
 
     let difflight = DiffuseLight::from_color(Vec3::splat(4.0));
-    objects.push(Box::new(Rect {
+    let light = Rect {
         in_plane: XY,
         a0: 3.0,
         a1: 5.0,
@@ -443,12 +456,17 @@ fn simple_light() -> Scene {
         b1: 3.0,
         k: -2.0,
         material: difflight,
-    }));
+    };
+    objects.push(Box::new(light.clone()));
 
     Scene {
         world: HittableList { objects },
+        lights: HittableList {
+            objects: vec![Box::new(light)],
+        },
+        delta_lights: Vec::new(),
         camera_config: simple_light_camera(),
-        background_color: Vec3::zero(),
+        environment: EnvironmentLight::from_color(Vec3::zero()),
     }
 }
 
@@ -479,7 +497,7 @@ fn cornell_box() -> Scene {
         k: 0.0,
         material: red,
     }));
-    objects.push(Box::new(Rect {
+    let light = Rect {
         in_plane: XZ,
         a0: 213.0,
         a1: 343.0,
@@ -487,7 +505,8 @@ fn cornell_box() -> Scene {
         b1: 332.0,
         k: 554.0,
         material: light,
-    }));
+    };
+    objects.push(Box::new(light.clone()));
     objects.push(Box::new(Rect {
         in_plane: XZ,
         a0: 0.0,
@@ -531,8 +550,12 @@ fn cornell_box() -> Scene {
 
     Scene {
         world: HittableList { objects },
+        lights: HittableList {
+            objects: vec![Box::new(light)],
+        },
+        delta_lights: Vec::new(),
         camera_config: cornell_box_camera(),
-        background_color: Vec3::zero(),
+        environment: EnvironmentLight::from_color(Vec3::zero()),
     }
 }
 
@@ -605,7 +628,7 @@ fn final_scene() -> Scene {
     objects.push(Box::new(BVH::build(boxes, 0.0, 1.0)));
 
     let light = DiffuseLight::from_color(Vec3::splat(7.0));
-    objects.push(Box::new(Rect {
+    let light = Rect {
         in_plane: XZ,
         a0: 123.0,
         a1: 423.0,
@@ -613,7 +636,8 @@ fn final_scene() -> Scene {
         b1: 412.0,
         k: 445.0,
         material: light,
-    }));
+    };
+    objects.push(Box::new(light.clone()));
 
     let center0 = Vec3::new(400.0, 400.0, 200.0);
     let center1 = center0 + Vec3::new(30.0, 0.0, 0.0);
@@ -627,9 +651,7 @@ fn final_scene() -> Scene {
         material: moving_sphere_material,
     }));
 
-    let glass = Dielectric {
-        index_refraction: 0.5,
-    };
+    let glass = Dielectric::new(0.5);
     objects.push(Box::new(Sphere {
         center: Vec3::new(260.0, 150.0, 45.0),
         radius: 50.0,
@@ -638,10 +660,7 @@ fn final_scene() -> Scene {
     objects.push(Box::new(Sphere {
         center: Vec3::new(0.0, 150.0, 145.0),
         radius: 50.0,
-        material: Metal {
-            albedo: Vec3::new(0.8, 0.8, 0.9),
-            fuzz: 1.0,
-        },
+        material: Metal::from_color(Vec3::new(0.8, 0.8, 0.9), 1.0),
     }));
 
     let boundary = Sphere {
@@ -699,8 +718,12 @@ fn final_scene() -> Scene {
 
     Scene {
         world: HittableList { objects },
+        lights: HittableList {
+            objects: vec![Box::new(light)],
+        },
+        delta_lights: Vec::new(),
         camera_config: final_scene_camera(),
-        background_color: Vec3::zero(),
+        environment: EnvironmentLight::from_color(Vec3::zero()),
     }
 }
 
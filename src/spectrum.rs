@@ -0,0 +1,209 @@
+//! Helpers to render with wavelengths of light instead of RGB colors.
+//!
+//! The colors of the scene are RGB, so they are upsampled to a smooth
+//! reflectance spectrum with [rgb_to_spectrum] (Smits' method) when a
+//! single wavelength is needed. The radiance carried at a wavelength is
+//! turned back into RGB through the CIE 1931 color matching functions
+//! with a [SpectralResponse].
+
+use vec3::Vec3;
+
+/// Shortest wavelength (in nanometers) that is rendered
+pub const LAMBDA_MIN: f64 = 380.0;
+/// Longest wavelength (in nanometers) that is rendered
+pub const LAMBDA_MAX: f64 = 720.0;
+/// Wavelength (in nanometers) of the sodium d-line. Refraction indices are
+/// usually given at it, so it is used when rendering without wavelengths.
+pub const LAMBDA_D: f64 = 587.6;
+
+/// Samples of Smits' basis spectra, equally spaced in [LAMBDA_MIN, LAMBDA_MAX]
+const SMITS_WHITE: [f64; 10] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f64; 10] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f64; 10] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const SMITS_YELLOW: [f64; 10] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f64; 10] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f64; 10] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f64; 10] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+/// Linearly interpolates the samples of a basis spectrum at `wavelength`
+fn eval_basis(samples: &[f64; 10], wavelength: f64) -> f64 {
+    let n = samples.len();
+    // Samples are at the center of each bin
+    let x = (wavelength - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN) * n as f64 - 0.5;
+    if x <= 0.0 {
+        return samples[0];
+    }
+    let i = x as usize;
+    if i >= n - 1 {
+        return samples[n - 1];
+    }
+    let t = x - i as f64;
+    samples[i] * (1.0 - t) + samples[i + 1] * t
+}
+
+/// Returns the value at `wavelength` of a smooth spectrum whose color is
+/// `rgb`, using Smits' method. It is linear with the intensity of the
+/// color, so it can be used with reflectances and light alike.
+pub fn rgb_to_spectrum(rgb: &Vec3, wavelength: f64) -> f64 {
+    let (r, g, b) = (rgb.x(), rgb.y(), rgb.z());
+    let basis = |samples: &[f64; 10]| eval_basis(samples, wavelength);
+
+    if r <= g && r <= b {
+        let mut value = r * basis(&SMITS_WHITE);
+        if g <= b {
+            value += (g - r) * basis(&SMITS_CYAN) + (b - g) * basis(&SMITS_BLUE);
+        } else {
+            value += (b - r) * basis(&SMITS_CYAN) + (g - b) * basis(&SMITS_GREEN);
+        }
+        value
+    } else if g <= r && g <= b {
+        let mut value = g * basis(&SMITS_WHITE);
+        if r <= b {
+            value += (r - g) * basis(&SMITS_MAGENTA) + (b - r) * basis(&SMITS_BLUE);
+        } else {
+            value += (b - g) * basis(&SMITS_MAGENTA) + (r - b) * basis(&SMITS_RED);
+        }
+        value
+    } else {
+        let mut value = b * basis(&SMITS_WHITE);
+        if r <= g {
+            value += (r - b) * basis(&SMITS_YELLOW) + (g - r) * basis(&SMITS_GREEN);
+        } else {
+            value += (g - b) * basis(&SMITS_YELLOW) + (r - g) * basis(&SMITS_RED);
+        }
+        value
+    }
+}
+
+/// Piecewise gaussian used by the fit of the color matching functions
+fn gaussian(x: f64, mu: f64, sigma_low: f64, sigma_high: f64) -> f64 {
+    let sigma = if x < mu { sigma_low } else { sigma_high };
+    let t = (x - mu) / sigma;
+    (-0.5 * t * t).exp()
+}
+
+/// Returns the CIE 1931 color matching functions (x̄, ȳ, z̄) at `wavelength`
+/// (in nanometers). It uses the multi-lobe fit of Wyman, Sloan and Shirley.
+pub fn cie_xyz(wavelength: f64) -> Vec3 {
+    let l = wavelength;
+    Vec3::new(
+        1.056 * gaussian(l, 599.8, 37.9, 31.0) + 0.362 * gaussian(l, 442.0, 16.0, 26.7)
+            - 0.065 * gaussian(l, 501.1, 20.4, 26.2),
+        0.821 * gaussian(l, 568.8, 46.9, 40.5) + 0.286 * gaussian(l, 530.9, 16.3, 31.1),
+        1.217 * gaussian(l, 437.0, 11.8, 36.0) + 0.681 * gaussian(l, 459.0, 26.0, 13.8),
+    )
+}
+
+/// Converts a CIE XYZ color to linear sRGB
+pub fn xyz_to_rgb(xyz: &Vec3) -> Vec3 {
+    let (x, y, z) = (xyz.x(), xyz.y(), xyz.z());
+    Vec3::new(
+        3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+        -0.9692660 * x + 1.8760108 * y + 0.0415560 * z,
+        0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+    )
+}
+
+/// Returns the linear sRGB color of a black body at `temperature` (in
+/// kelvin), normalized to a luminance of `1.0`. Colors the sRGB gamut can't
+/// show (like the reds below 1900K) are clipped.
+pub fn blackbody(temperature: f64) -> Vec3 {
+    // Planck's law, without the constants that cancel with the normalization
+    let planck = |wavelength: f64| {
+        let l = wavelength * 1e-9;
+        1.0 / (l.powi(5) * ((0.014387769 / (l * temperature)).exp() - 1.0))
+    };
+    let steps = (LAMBDA_MAX - LAMBDA_MIN) as usize;
+    let step = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+    let xyz: Vec3 = (0..steps)
+        .map(|i| {
+            let wavelength = LAMBDA_MIN + (i as f64 + 0.5) * step;
+            cie_xyz(wavelength) * planck(wavelength)
+        })
+        .sum();
+    let rgb = xyz_to_rgb(&xyz).map(|c| c.max(0.0));
+    rgb / rgb.luminance()
+}
+
+/// Converts radiance at single wavelengths into RGB colors
+///
+/// It is normalized so a constant spectrum of value `1.0` becomes white
+/// (`Vec3::one()`), which makes [rgb_to_spectrum] of a gray round trip.
+#[derive(Clone)]
+pub struct SpectralResponse {
+    /// RGB color of the constant spectrum of value `1.0`
+    white: Vec3,
+}
+
+impl Default for SpectralResponse {
+    fn default() -> Self {
+        // Integrates the response in steps of 1nm
+        let steps = (LAMBDA_MAX - LAMBDA_MIN) as usize;
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+        let white = (0..steps)
+            .map(|i| xyz_to_rgb(&cie_xyz(LAMBDA_MIN + (i as f64 + 0.5) * step)) * step)
+            .sum();
+        SpectralResponse { white }
+    }
+}
+
+impl SpectralResponse {
+    /// Returns the RGB color of `radiance` at `wavelength` divided by
+    /// `pdf`, the probability density with which the wavelength was chosen.
+    pub fn to_rgb(&self, radiance: f64, wavelength: f64, pdf: f64) -> Vec3 {
+        xyz_to_rgb(&cie_xyz(wavelength)).zip_with(self.white, |c, w| c / w) * (radiance / pdf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blackbody_colors() {
+        let d65 = blackbody(6504.0);
+        assert!((d65.luminance() - 1.0).abs() < 1e-9);
+        assert!((d65 - Vec3::splat(1.0)).length() < 0.1);
+
+        let candle = blackbody(1850.0);
+        assert!(candle.x() > candle.y() && candle.y() > candle.z());
+    }
+
+    #[test]
+    fn gray_spectrum_is_flat() {
+        for i in 0..=34 {
+            let wavelength = LAMBDA_MIN + i as f64 * 10.0;
+            let value = rgb_to_spectrum(&Vec3::new(0.5, 0.5, 0.5), wavelength);
+            assert!((value - 0.5).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn white_round_trip() {
+        let response = SpectralResponse::default();
+        let steps = 3400;
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+        let pdf = 1.0 / (LAMBDA_MAX - LAMBDA_MIN);
+        let rgb = (0..steps)
+            .map(|i| {
+                let wavelength = LAMBDA_MIN + (i as f64 + 0.5) * step;
+                response.to_rgb(1.0, wavelength, pdf) / steps as f64
+            })
+            .sum::<Vec3>();
+        assert!((rgb - Vec3::one()).length() < 1e-2);
+    }
+}
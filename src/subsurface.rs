@@ -0,0 +1,152 @@
+//! Subsurface scattering as a random walk inside an object.
+//!
+//! [Subsurface] wraps a [Hittable] instead of being a [Material](crate::material::Material)
+//! because the walk has to know where it leaves the object: every step is
+//! tested against the boundary, and a material only sees the [HitRecord]
+//! of one point. As an object, the scattering events inside it are ordinary
+//! hits with a volumetric material, the same as in a
+//! [ConstantMedium](crate::constant_medium::ConstantMedium), so every
+//! integrator renders it without knowing about it.
+
+use crate::aabb::AABB;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Isotropic;
+use crate::ray::Ray;
+use crate::texture::{SolidColor, Texture};
+use crate::Clampable;
+use vec3::Vec3;
+
+/// Single scattering albedo that makes a medium look like a color after
+/// light bounces many times inside it (van de Hulst's inversion)
+#[derive(Clone)]
+struct ScatteringAlbedo<T: Texture>(T);
+
+impl<T: Texture> Texture for ScatteringAlbedo<T> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        self.0.value(u, v, p).map(|c| {
+            let c = c.clamp_(0.0, 1.0);
+            let s = 4.09712 + 4.20863 * c - (9.59217 + 41.6808 * c + 17.7126 * c * c).sqrt();
+            1.0 - s * s
+        })
+    }
+}
+
+/// Object with subsurface scattering
+///
+/// Light that crosses the surface of the boundary walks randomly inside it,
+/// scattering in random directions until it leaves the object again. Gives
+/// the soft, glowing look of skin, wax, marble or milk.
+///
+/// The surface keeps the material of the boundary, which decides how light
+/// gets in and out: a [Dielectric](crate::material::Dielectric) (rough
+/// or smooth) usually. Like [ConstantMedium](crate::constant_medium::ConstantMedium),
+/// the boundary has to be convex.
+pub struct Subsurface<H: Hittable, T: Texture> {
+    boundary: H,
+    phase_function: Isotropic<ScatteringAlbedo<T>>,
+    mean_free_path: f64,
+}
+
+impl<H: Hittable, T: Texture> Subsurface<H, T> {
+    /// Creates a new [Subsurface] object inside `boundary`. The object looks
+    /// like the `color` texture once light has scattered many times, and
+    /// light travels `mean_free_path` between scattering events on average.
+    /// The shorter it is, the more opaque the object looks.
+    pub fn new(boundary: H, color: T, mean_free_path: f64) -> Self {
+        Self {
+            boundary,
+            phase_function: Isotropic {
+                albedo: ScatteringAlbedo(color),
+            },
+            mean_free_path,
+        }
+    }
+}
+
+impl<H: Hittable> Subsurface<H, SolidColor> {
+    /// Creates a new [Subsurface] object with the same color everywhere
+    pub fn from_color(boundary: H, color: Vec3, mean_free_path: f64) -> Self {
+        Self::new(boundary, SolidColor { color }, mean_free_path)
+    }
+}
+
+impl<H: Hittable, T: Texture> Hittable for Subsurface<H, T> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let surface_hit = self.boundary.hit(r, t_min, t_max);
+
+        // The ray travels inside when it starts between the entry and exit points
+        let inside = self
+            .boundary
+            .hit(r, f64::NEG_INFINITY, f64::INFINITY)
+            .filter(|entry| entry.t < t_min)
+            .and_then(|entry| self.boundary.hit(r, entry.t + 0.0001, f64::INFINITY))
+            .is_some_and(|exit| exit.t > t_min);
+        if !inside {
+            return surface_hit;
+        }
+
+        let ray_length = r.direction.length();
+        let hit_distance = -self.mean_free_path * rand::random::<f64>().ln();
+        let t = t_min + hit_distance / ray_length;
+        if t >= surface_hit.as_ref().map_or(t_max, |hit| hit.t) {
+            return surface_hit;
+        }
+
+        let point = r.at(t);
+        let normal = Vec3::new(1.0, 0.0, 0.0); // Some arbitrary value, and u, v
+        Some(
+            HitRecord::new(r, t, 1.0, 1.0, point, normal, &self.phase_function)
+                .with_arbitrary_tangents(),
+        )
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        self.boundary.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::object::Sphere;
+
+    #[test]
+    fn scattering_albedo() {
+        let albedo = ScatteringAlbedo(SolidColor {
+            color: Vec3::new(0.0, 0.5, 1.0),
+        })
+        .value(0.0, 0.0, &Vec3::zero());
+        assert!(albedo.x().abs() < 1e-3);
+        assert!(albedo.y() > 0.5 && albedo.y() < 1.0);
+        assert!((albedo.z() - 1.0).abs() < 1e-3);
+    }
+    #[test]
+    fn random_walk_exits_on_the_surface() {
+        let center = Vec3::new(1.0, 2.0, 3.0);
+        let object = Subsurface::from_color(
+            Sphere {
+                center,
+                radius: 1.0,
+                material: Lambertian::from_color(Vec3::one()),
+            },
+            Vec3::new(0.9, 0.5, 0.1),
+            0.1,
+        );
+        for _ in 0..100 {
+            let mut r = Ray::new(center, Vec3::random_unit_vector(), 0.0);
+            let mut throughput = Vec3::one();
+            loop {
+                let hit = object.hit(&r, 0.0001, f64::INFINITY).unwrap();
+                if !hit.material.is_volumetric() {
+                    assert!(((hit.point - center).length() - 1.0).abs() < 1e-6);
+                    break;
+                }
+                let (attenuation, scattered) = hit.material.scatter(&r, &hit).unwrap();
+                throughput *= attenuation;
+                assert!(throughput.iter().all(|c| (0.0..=1.0).contains(c)));
+                r = scattered;
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use super::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Cache of the images loaded as textures
+///
+/// Hands out shared [ImageTexture]s, so an image used by many materials or
+/// objects is loaded and kept in memory only once. Images are keyed by
+/// their path, and loaded as color and as data separately.
+#[derive(Default)]
+pub struct TextureCache {
+    textures: HashMap<(PathBuf, bool), Arc<ImageTexture>>,
+}
+
+impl TextureCache {
+    /// Creates an empty [TextureCache]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the texture with the colors of the image of the given path,
+    /// loading it the first time. See [ImageTexture::new].
+    pub fn image(&mut self, filename: &str) -> Result<Arc<ImageTexture>, ImageTextureError> {
+        self.get(filename, true)
+    }
+
+    /// Returns the texture with the data of the image of the given path,
+    /// loading it the first time. See [ImageTexture::data].
+    pub fn data(&mut self, filename: &str) -> Result<Arc<ImageTexture>, ImageTextureError> {
+        self.get(filename, false)
+    }
+
+    /// Number of images in the cache
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Whether the cache has no images
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    fn get(&mut self, filename: &str, color: bool) -> Result<Arc<ImageTexture>, ImageTextureError> {
+        // The same file may be reached through different paths
+        let path = Path::new(filename);
+        let key = (
+            path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+            color,
+        );
+        if let Some(texture) = self.textures.get(&key) {
+            return Ok(Arc::clone(texture));
+        }
+
+        let texture = Arc::new(if color {
+            ImageTexture::new(filename)?
+        } else {
+            ImageTexture::data(filename)?
+        });
+        self.textures.insert(key, Arc::clone(&texture));
+        Ok(texture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_textures() {
+        let path = std::env::temp_dir().join("ray_tracing_cached_texture.png");
+        image::RgbImage::from_pixel(2, 2, image::Rgb([255, 0, 0]))
+            .save(&path)
+            .unwrap();
+        let filename = path.to_str().unwrap();
+
+        let mut cache = TextureCache::new();
+        let a = cache.image(filename).unwrap();
+        let b = cache.image(filename).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        let data = cache.data(filename).unwrap();
+        assert!(!Arc::ptr_eq(&a, &data));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.image("missing.png").is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
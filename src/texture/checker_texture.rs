@@ -17,15 +17,125 @@ impl CheckerTexture<SolidColor, SolidColor> {
     }
 }
 
-impl<T1: Texture, T2: Texture> Texture for CheckerTexture<T1, T2> {
-    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+impl<T1: Texture, T2: Texture> CheckerTexture<T1, T2> {
+    /// Whether the point `p` falls in an `odd` square
+    fn is_odd(p: &Vec3) -> bool {
         let p_10 = p.scale(10.0);
         let sines = p_10.x().sin() * p_10.y().sin() * p_10.z().sin();
+        sines < 0.0
+    }
+}
 
-        if sines < 0.0 {
+impl<T1: Texture, T2: Texture> Texture for CheckerTexture<T1, T2> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        if Self::is_odd(p) {
             self.odd.value(u, v, p)
         } else {
             self.even.value(u, v, p)
         }
     }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        if Self::is_odd(p) {
+            self.odd.filtered(u, v, p, width)
+        } else {
+            self.even.filtered(u, v, p, width)
+        }
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        if Self::is_odd(&hit.point) {
+            self.odd.value_at(hit)
+        } else {
+            self.even.value_at(hit)
+        }
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        if Self::is_odd(p) {
+            self.odd.alpha(u, v, p)
+        } else {
+            self.even.alpha(u, v, p)
+        }
+    }
+}
+
+/// Checker pattern of 2 other textures in the surface coordinates
+///
+/// Unlike [CheckerTexture], the squares follow the surface and do not
+/// depend on where the object is.
+#[derive(Clone)]
+pub struct UvChecker<T1: Texture, T2: Texture> {
+    pub odd: T1,
+    pub even: T2,
+    /// Number of squares along `u` and `v`
+    pub squares: (f64, f64),
+}
+
+impl UvChecker<SolidColor, SolidColor> {
+    /// Creates a [UvChecker] composed of 2 [SolidColor] textures
+    pub fn from_colors(odd: Vec3, even: Vec3, squares: (f64, f64)) -> Self {
+        Self {
+            odd: SolidColor { color: odd },
+            even: SolidColor { color: even },
+            squares,
+        }
+    }
+}
+
+impl<T1: Texture, T2: Texture> UvChecker<T1, T2> {
+    /// Whether the coordinates (`u`, `v`) fall in an `odd` square
+    fn is_odd(&self, u: f64, v: f64) -> bool {
+        let i = (u * self.squares.0).floor() + (v * self.squares.1).floor();
+        i.rem_euclid(2.0) >= 1.0
+    }
+}
+
+impl<T1: Texture, T2: Texture> Texture for UvChecker<T1, T2> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        if self.is_odd(u, v) {
+            self.odd.value(u, v, p)
+        } else {
+            self.even.value(u, v, p)
+        }
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        if self.is_odd(u, v) {
+            self.odd.filtered(u, v, p, width)
+        } else {
+            self.even.filtered(u, v, p, width)
+        }
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        if self.is_odd(hit.u, hit.v) {
+            self.odd.value_at(hit)
+        } else {
+            self.even.value_at(hit)
+        }
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        if self.is_odd(u, v) {
+            self.odd.alpha(u, v, p)
+        } else {
+            self.even.alpha(u, v, p)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uv_checker() {
+        let checker = UvChecker::from_colors(Vec3::one(), Vec3::zero(), (4.0, 2.0));
+        let p = Vec3::zero();
+        assert_eq!(checker.value(0.1, 0.1, &p), Vec3::zero());
+        assert_eq!(checker.value(0.3, 0.1, &p), Vec3::one());
+        assert_eq!(checker.value(0.3, 0.6, &p), Vec3::zero());
+        assert_eq!(checker.value(-0.1, 0.1, &p), Vec3::one());
+    }
 }
@@ -1,11 +1,173 @@
 use super::*;
-use crate::Clampable;
 use image::io::Reader as ImageReader;
+use image::ColorType;
+
+/// How surface coordinates outside [`0.0`, `1.0`] are mapped into an image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WrapMode {
+    /// The image tiles the surface
+    Repeat,
+    /// The image tiles the surface, flipped every other time
+    Mirror,
+    /// The texels of the edges are stretched
+    Clamp,
+}
+
+impl WrapMode {
+    /// Index of the texel used for index `i` of an image of `size` texels
+    fn apply(self, i: i64, size: usize) -> usize {
+        let size = size as i64;
+        let i = match self {
+            WrapMode::Repeat => i.rem_euclid(size),
+            WrapMode::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size {
+                    i
+                } else {
+                    2 * size - 1 - i
+                }
+            }
+            WrapMode::Clamp => i.clamp(0, size - 1),
+        };
+        i as usize
+    }
+}
+
+/// How the texels around the surface coordinates of a lookup are combined
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    /// The closest texel. Blocky up close and aliased at distance.
+    Nearest,
+    /// Interpolation of the 4 closest texels. Smooth up close.
+    Bilinear,
+    /// Bilinear interpolation in the 2 levels of the mipmap closest to the
+    /// footprint of the ray, interpolated too. Smooth at any distance.
+    Trilinear,
+}
+
+/// RGBA color in [`0.0`, `1.0`]
+type Rgba = [f64; 4];
+
+/// Linear interpolation between 2 colors
+fn lerp(a: Rgba, b: Rgba, t: f64) -> Rgba {
+    let mut color = a;
+    for (c, b) in color.iter_mut().zip(b.iter()) {
+        *c += (b - *c) * t;
+    }
+    color
+}
+
+/// Decodes a channel of a color encoded with the sRGB transfer function
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Level of the mipmap of an image
+#[derive(Clone)]
+struct MipLevel {
+    width: usize,
+    height: usize,
+    /// Linear RGBA texels, by rows from the top of the image
+    texels: Vec<[f32; 4]>,
+}
+
+impl MipLevel {
+    /// Creates the level of an image, decoding the sRGB colors if `srgb`
+    fn from_image(img: &image::Rgba32FImage, srgb: bool) -> Self {
+        let texels = img
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                if srgb {
+                    [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+                } else {
+                    [r, g, b, a]
+                }
+            })
+            .collect();
+        Self {
+            width: img.width() as usize,
+            height: img.height() as usize,
+            texels,
+        }
+    }
+
+    /// Returns the next level of the mipmap, half the size with each texel
+    /// the average of a block of 2x2
+    fn downsample(&self) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut texels = Vec::with_capacity(width * height);
+        for j in 0..height {
+            for i in 0..width {
+                let mut texel = [0.0; 4];
+                for (di, dj) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let x = (2 * i + di).min(self.width - 1);
+                    let y = (2 * j + dj).min(self.height - 1);
+                    let other = self.texels[y * self.width + x];
+                    for (c, o) in texel.iter_mut().zip(other.iter()) {
+                        *c += 0.25 * o;
+                    }
+                }
+                texels.push(texel);
+            }
+        }
+        Self {
+            width,
+            height,
+            texels,
+        }
+    }
+
+    fn texel(&self, i: i64, j: i64, wrap: WrapMode) -> Rgba {
+        let i = wrap.apply(i, self.width);
+        let j = wrap.apply(j, self.height);
+        self.texels[j * self.width + i].map(|c| c as f64)
+    }
+
+    fn nearest(&self, u: f64, v: f64, wrap: WrapMode) -> Rgba {
+        let x = u * self.width as f64;
+        let y = (1.0 - v) * self.height as f64;
+        self.texel(x.floor() as i64, y.floor() as i64, wrap)
+    }
+
+    fn bilinear(&self, u: f64, v: f64, wrap: WrapMode) -> Rgba {
+        // Texel centers are at half coords
+        let x = u * self.width as f64 - 0.5;
+        let y = (1.0 - v) * self.height as f64 - 0.5;
+        let (i, j) = (x.floor(), y.floor());
+        let (tx, ty) = (x - i, y - j);
+        let (i, j) = (i as i64, j as i64);
+        lerp(
+            lerp(self.texel(i, j, wrap), self.texel(i + 1, j, wrap), tx),
+            lerp(
+                self.texel(i, j + 1, wrap),
+                self.texel(i + 1, j + 1, wrap),
+                tx,
+            ),
+            ty,
+        )
+    }
+}
 
 /// Texture of an image
+///
+/// The alpha channel of the image, if any, is loaded too and returned by
+/// [alpha](Texture::alpha), so the texture can be used as a cut-out mask.
+///
+/// By default the image is clamped at its edges and filtered trilinearly,
+/// see [with_wrap](ImageTexture::with_wrap) and [with_filter](ImageTexture::with_filter).
 #[derive(Clone)]
 pub struct ImageTexture {
-    img: image::RgbImage,
+    /// Mipmap of the image, from the full size level to a single texel.
+    /// Clones of the texture share it.
+    levels: Arc<Vec<MipLevel>>,
+    wrap: WrapMode,
+    filter: Filter,
 }
 
 /// Error that [ImageTexture::new] can return
@@ -30,41 +192,138 @@ impl From<image::ImageError> for ImageTextureError {
 }
 
 impl ImageTexture {
-    /// Creates a new [ImageTexture] reading the image of the given path.
+    /// Creates a new [ImageTexture] with the colors of the image of the
+    /// given path. Formats of 8 or 16 bits per channel (like PNG, JPEG or
+    /// TIFF) are taken as sRGB and decoded to linear colors, while floating
+    /// point ones (Radiance HDR and OpenEXR) are already linear.
     pub fn new(filename: &str) -> Result<Self, ImageTextureError> {
-        let img = ImageReader::open(filename)?.decode()?.into_rgb8();
-        Ok(Self { img })
+        Self::load(filename, true)
+    }
+
+    /// Creates a new [ImageTexture] with the data of the image of the given
+    /// path, read as is without decoding any color. For textures that are
+    /// not colors, like normal maps or roughness.
+    pub fn data(filename: &str) -> Result<Self, ImageTextureError> {
+        Self::load(filename, false)
+    }
+
+    fn load(filename: &str, color: bool) -> Result<Self, ImageTextureError> {
+        let img = ImageReader::open(filename)?.decode()?;
+        let srgb = color && !matches!(img.color(), ColorType::Rgb32F | ColorType::Rgba32F);
+        let img = img.into_rgba32f();
+
+        let mut levels = vec![MipLevel::from_image(&img, srgb)];
+        while let Some(last) = levels.last().filter(|l| l.width > 1 || l.height > 1) {
+            let next = last.downsample();
+            levels.push(next);
+        }
+        Ok(Self {
+            levels: Arc::new(levels),
+            wrap: WrapMode::Clamp,
+            filter: Filter::Trilinear,
+        })
+    }
+
+    /// Sets how the coordinates outside the image are wrapped
+    pub fn with_wrap(self, wrap: WrapMode) -> Self {
+        Self { wrap, ..self }
+    }
+
+    /// Sets how the texels are filtered
+    pub fn with_filter(self, filter: Filter) -> Self {
+        Self { filter, ..self }
+    }
+
+    /// Width and height of the image in texels
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.levels[0].width, self.levels[0].height)
+    }
+
+    /// Color of the image at the surface coordinates (`u`, `v`) over a
+    /// footprint of `width`
+    fn sample(&self, u: f64, v: f64, width: f64) -> Rgba {
+        let base = &self.levels[0];
+        match self.filter {
+            Filter::Nearest => base.nearest(u, v, self.wrap),
+            Filter::Bilinear => base.bilinear(u, v, self.wrap),
+            Filter::Trilinear => {
+                // Level where the footprint covers about a texel
+                let texels = width * base.width.max(base.height) as f64;
+                let top = (self.levels.len() - 1) as f64;
+                let level = if texels > 1.0 {
+                    texels.log2().min(top)
+                } else {
+                    0.0
+                };
+                let lower = level.floor() as usize;
+                let upper = (lower + 1).min(self.levels.len() - 1);
+                lerp(
+                    self.levels[lower].bilinear(u, v, self.wrap),
+                    self.levels[upper].bilinear(u, v, self.wrap),
+                    level.fract(),
+                )
+            }
+        }
     }
 }
 
 impl Texture for ImageTexture {
-    fn value(&self, u: f64, v: f64, _p: &Vec3) -> Vec3 {
-        let u = u.clamp_(0.0, 1.0);
-        let v = 1.0 - v.clamp_(0.0, 1.0);
-
-        let width = self.img.width();
-        let height = self.img.height();
-
-        let i = {
-            let i = (u * (width as f64)) as u32;
-            if i < width {
-                i
-            } else {
-                width - 1
-            }
-        };
-        let j = {
-            let j = (v * (height as f64)) as u32;
-            if j < height {
-                j
-            } else {
-                height - 1
-            }
-        };
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        self.filtered(u, v, p, 0.0)
+    }
+
+    fn filtered(&self, u: f64, v: f64, _p: &Vec3, width: f64) -> Vec3 {
+        let [r, g, b, _] = self.sample(u, v, width);
+        Vec3::new(r, g, b)
+    }
+
+    fn alpha(&self, u: f64, v: f64, _p: &Vec3) -> f64 {
+        self.sample(u, v, 0.0)[3]
+    }
+}
 
-        let color_scale = 1.0 / 255.0;
-        let pixel = self.img.get_pixel(i, j);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Vec3::new(pixel[0] as f64, pixel[1] as f64, pixel[2] as f64) * color_scale
+    #[test]
+    fn load_16_bit_data() {
+        let path = std::env::temp_dir().join("ray_tracing_16_bit_texture.png");
+        let img = image::ImageBuffer::<image::Rgba<u16>, _>::from_pixel(
+            2,
+            2,
+            image::Rgba([0, 32768, 65535, 65535]),
+        );
+        img.save(&path).unwrap();
+
+        let texture = ImageTexture::data(path.to_str().unwrap()).unwrap();
+        let color = texture.value(0.5, 0.5, &Vec3::zero());
+        assert!((color - Vec3::new(0.0, 0.5, 1.0)).length() < 1e-4);
+
+        let texture = ImageTexture::new(path.to_str().unwrap()).unwrap();
+        let color = texture.value(0.5, 0.5, &Vec3::zero());
+        assert!((color.y() - 0.214).abs() < 1e-3);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn wrap_modes() {
+        let indices = |wrap: WrapMode| (-3..7).map(|i| wrap.apply(i, 4)).collect::<Vec<_>>();
+        assert_eq!(indices(WrapMode::Repeat), [1, 2, 3, 0, 1, 2, 3, 0, 1, 2]);
+        assert_eq!(indices(WrapMode::Mirror), [2, 1, 0, 0, 1, 2, 3, 3, 2, 1]);
+        assert_eq!(indices(WrapMode::Clamp), [0, 0, 0, 0, 1, 2, 3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn mipmap_levels() {
+        let level = MipLevel {
+            width: 2,
+            height: 2,
+            texels: vec![[0.0; 4], [1.0; 4], [0.5; 4], [0.5; 4]],
+        };
+        // Bilinear interpolation goes through the texel centers
+        assert_eq!(level.bilinear(0.25, 0.75, WrapMode::Clamp), [0.0; 4]);
+        assert_eq!(level.bilinear(0.5, 0.5, WrapMode::Clamp), [0.5; 4]);
+        assert_eq!(level.downsample().texels, vec![[0.5; 4]]);
     }
 }
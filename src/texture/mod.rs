@@ -2,6 +2,8 @@
 //!
 //! More textures can be created implementing the [Texture] trait.
 
+use crate::hittable::HitRecord;
+use std::sync::Arc;
 use vec3::Vec3;
 
 /// Trait that the supported textures have to implement.
@@ -9,6 +11,47 @@ pub trait Texture: Sync + Send {
     /// Color of the texture in the surface coordinates (`u`, `v`)
     /// and the hit point `p`.
     fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3;
+
+    #[allow(unused)]
+    /// Opacity [`0.0`, `1.0`] of the texture in the surface coordinates
+    /// (`u`, `v`) and the hit point `p`. Textures without an alpha channel
+    /// are fully opaque.
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        1.0
+    }
+
+    #[allow(unused)]
+    /// Color of the texture averaged over a square of side `width` (in
+    /// surface coordinates) around (`u`, `v`). Textures that are not
+    /// filtered return their [value](Texture::value).
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        self.value(u, v, p)
+    }
+
+    /// Color of the texture at a hit, filtered over the footprint of the ray
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        self.filtered(hit.u, hit.v, &hit.point, hit.uv_footprint())
+    }
+}
+
+/// Shared textures, so the same texture can drive several parameters or
+/// materials. Allows using `Arc<dyn Texture>` where a [Texture] is expected.
+impl<T: Texture + ?Sized> Texture for Arc<T> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        (**self).value(u, v, p)
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        (**self).alpha(u, v, p)
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        (**self).filtered(u, v, p, width)
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        (**self).value_at(hit)
+    }
 }
 
 mod solid_color;
@@ -19,3 +62,13 @@ mod noise_texture;
 pub use noise_texture::*;
 mod image_texture;
 pub use image_texture::*;
+mod uv_transform;
+pub use uv_transform::*;
+mod projection;
+pub use projection::*;
+mod cache;
+pub use cache::*;
+mod procedural;
+pub use procedural::*;
+mod nodes;
+pub use nodes::*;
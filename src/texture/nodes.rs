@@ -0,0 +1,411 @@
+use super::*;
+use crate::Clampable;
+
+/// Product of 2 textures, channel by channel
+///
+/// The alpha is the product of the alphas too.
+#[derive(Clone)]
+pub struct Multiply<A: Texture, B: Texture> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A: Texture, B: Texture> Texture for Multiply<A, B> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        self.a.value(u, v, p) * self.b.value(u, v, p)
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        self.a.alpha(u, v, p) * self.b.alpha(u, v, p)
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        self.a.filtered(u, v, p, width) * self.b.filtered(u, v, p, width)
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        self.a.value_at(hit) * self.b.value_at(hit)
+    }
+}
+
+/// Sum of 2 textures, channel by channel
+///
+/// The alpha is the highest of both.
+#[derive(Clone)]
+pub struct Add<A: Texture, B: Texture> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A: Texture, B: Texture> Texture for Add<A, B> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        self.a.value(u, v, p) + self.b.value(u, v, p)
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        self.a.alpha(u, v, p).max(self.b.alpha(u, v, p))
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        self.a.filtered(u, v, p, width) + self.b.filtered(u, v, p, width)
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        self.a.value_at(hit) + self.b.value_at(hit)
+    }
+}
+
+/// Blend of 2 textures
+///
+/// Linear interpolation from `a` where the factor is `0.0` to `b` where it
+/// is `1.0`. Only the first channel of the factor is used, clamped to
+/// [`0.0`, `1.0`]. Alphas are blended the same way.
+#[derive(Clone)]
+pub struct Blend<A: Texture, B: Texture, F: Texture> {
+    pub a: A,
+    pub b: B,
+    pub factor: F,
+}
+
+impl<A: Texture, B: Texture> Blend<A, B, SolidColor> {
+    /// Blends 2 textures with the same factor everywhere
+    pub fn new(a: A, b: B, factor: f64) -> Self {
+        Self {
+            a,
+            b,
+            factor: SolidColor::gray(factor),
+        }
+    }
+}
+
+/// Linear interpolation from `a` to `b` by the first channel of `factor`
+fn lerp(a: Vec3, b: Vec3, factor: Vec3) -> Vec3 {
+    let t = factor.x().clamp_(0.0, 1.0);
+    a + (b - a) * t
+}
+
+impl<A: Texture, B: Texture, F: Texture> Texture for Blend<A, B, F> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        lerp(
+            self.a.value(u, v, p),
+            self.b.value(u, v, p),
+            self.factor.value(u, v, p),
+        )
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        lerp(
+            Vec3::splat(self.a.alpha(u, v, p)),
+            Vec3::splat(self.b.alpha(u, v, p)),
+            self.factor.value(u, v, p),
+        )
+        .x()
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        lerp(
+            self.a.filtered(u, v, p, width),
+            self.b.filtered(u, v, p, width),
+            self.factor.filtered(u, v, p, width),
+        )
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        lerp(
+            self.a.value_at(hit),
+            self.b.value_at(hit),
+            self.factor.value_at(hit),
+        )
+    }
+}
+
+/// Inverse of a texture: `1.0` minus each channel
+///
+/// The alpha is kept.
+#[derive(Clone)]
+pub struct Invert<T: Texture> {
+    pub texture: T,
+}
+
+impl<T: Texture> Texture for Invert<T> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        Vec3::one() - self.texture.value(u, v, p)
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        self.texture.alpha(u, v, p)
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        Vec3::one() - self.texture.filtered(u, v, p, width)
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        Vec3::one() - self.texture.value_at(hit)
+    }
+}
+
+/// Texture with each channel clamped to a range
+///
+/// The alpha is kept.
+#[derive(Clone)]
+pub struct Clamp<T: Texture> {
+    pub texture: T,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl<T: Texture> Clamp<T> {
+    /// Clamps a texture to [`0.0`, `1.0`]
+    pub fn new(texture: T) -> Self {
+        Self {
+            texture,
+            min: 0.0,
+            max: 1.0,
+        }
+    }
+
+    fn clamp(&self, color: Vec3) -> Vec3 {
+        color.map(|c| c.clamp_(self.min, self.max))
+    }
+}
+
+impl<T: Texture> Texture for Clamp<T> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        self.clamp(self.texture.value(u, v, p))
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        self.texture.alpha(u, v, p)
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        self.clamp(self.texture.filtered(u, v, p, width))
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        self.clamp(self.texture.value_at(hit))
+    }
+}
+
+/// Converts an RGB color to hue (in turns), saturation and value
+fn rgb_to_hsv(color: Vec3) -> (f64, f64, f64) {
+    let (r, g, b) = (color.x(), color.y(), color.z());
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let hue = if delta <= 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0) / 6.0
+    } else if max == g {
+        ((b - r) / delta + 2.0) / 6.0
+    } else {
+        ((r - g) / delta + 4.0) / 6.0
+    };
+    let saturation = if max > 0.0 { delta / max } else { 0.0 };
+    (hue, saturation, max)
+}
+
+/// Converts a hue (in turns), saturation and value to an RGB color
+fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> Vec3 {
+    let h = hue.rem_euclid(1.0) * 6.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    Vec3::new(r, g, b) + Vec3::splat(value - chroma)
+}
+
+/// Texture with its hue, saturation and value adjusted
+///
+/// The alpha is kept.
+#[derive(Clone)]
+pub struct HsvAdjust<T: Texture> {
+    pub texture: T,
+    /// Rotation of the hue, in turns (`0.5` gives the complementary colors)
+    pub hue: f64,
+    /// Factor of the saturation. `0.0` gives grays.
+    pub saturation: f64,
+    /// Factor of the value (brightness)
+    pub value: f64,
+}
+
+impl<T: Texture> HsvAdjust<T> {
+    /// Wraps a texture without changing its colors
+    pub fn new(texture: T) -> Self {
+        Self {
+            texture,
+            hue: 0.0,
+            saturation: 1.0,
+            value: 1.0,
+        }
+    }
+
+    fn adjust(&self, color: Vec3) -> Vec3 {
+        let (h, s, v) = rgb_to_hsv(color);
+        hsv_to_rgb(
+            h + self.hue,
+            (s * self.saturation).clamp_(0.0, 1.0),
+            v * self.value,
+        )
+    }
+}
+
+impl<T: Texture> Texture for HsvAdjust<T> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        self.adjust(self.texture.value(u, v, p))
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        self.texture.alpha(u, v, p)
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        self.adjust(self.texture.filtered(u, v, p, width))
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        self.adjust(self.texture.value_at(hit))
+    }
+}
+
+/// Texture mapped through a [ColorRamp]
+///
+/// Only the first channel of the texture is used, so extract the one
+/// wanted with an [ExtractChannel] first. The alpha is kept.
+#[derive(Clone)]
+pub struct ColorMap<T: Texture> {
+    pub texture: T,
+    pub ramp: ColorRamp,
+}
+
+impl<T: Texture> Texture for ColorMap<T> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        self.ramp.sample(self.texture.value(u, v, p).x())
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        self.texture.alpha(u, v, p)
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        self.ramp.sample(self.texture.filtered(u, v, p, width).x())
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        self.ramp.sample(self.texture.value_at(hit).x())
+    }
+}
+
+/// Channel of a texture that an [ExtractChannel] takes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    /// The [alpha](Texture::alpha) of the texture
+    Alpha,
+    /// The [luminance](Vec3::luminance) of the color
+    Luminance,
+}
+
+/// Gray texture with a single channel of another texture
+///
+/// The extracted channel is also returned as the alpha, so any texture
+/// (a procedural one, for example) can be the opacity of an
+/// [AlphaMask](crate::object::AlphaMask).
+#[derive(Clone)]
+pub struct ExtractChannel<T: Texture> {
+    pub texture: T,
+    pub channel: Channel,
+}
+
+impl<T: Texture> ExtractChannel<T> {
+    /// Channel of the texture, with its color and alpha looked up lazily
+    fn extract<C: FnOnce() -> Vec3, A: FnOnce() -> f64>(&self, color: C, alpha: A) -> f64 {
+        match self.channel {
+            Channel::Red => color().x(),
+            Channel::Green => color().y(),
+            Channel::Blue => color().z(),
+            Channel::Alpha => alpha(),
+            Channel::Luminance => color().luminance(),
+        }
+    }
+}
+
+impl<T: Texture> Texture for ExtractChannel<T> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        Vec3::splat(self.alpha(u, v, p))
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        self.extract(
+            || self.texture.value(u, v, p),
+            || self.texture.alpha(u, v, p),
+        )
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        Vec3::splat(self.extract(
+            || self.texture.filtered(u, v, p, width),
+            || self.texture.alpha(u, v, p),
+        ))
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        Vec3::splat(self.extract(
+            || self.texture.value_at(hit),
+            || self.texture.alpha(hit.u, hit.v, &hit.point),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hsv_conversion() {
+        for color in [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.2, 0.6, 0.4),
+            Vec3::new(0.3, 0.1, 0.9),
+            Vec3::splat(0.5),
+        ] {
+            let (h, s, v) = rgb_to_hsv(color);
+            assert!((hsv_to_rgb(h, s, v) - color).length() < 1e-12);
+        }
+        // Complementary color
+        let (h, s, v) = rgb_to_hsv(Vec3::new(1.0, 0.0, 0.0));
+        assert!((hsv_to_rgb(h + 0.5, s, v) - Vec3::new(0.0, 1.0, 1.0)).length() < 1e-12);
+    }
+
+    #[test]
+    fn node_graph() {
+        let p = Vec3::zero();
+        let red = SolidColor::rgb(1.0, 0.0, 0.0);
+        let gray = SolidColor::gray(0.5);
+        let graph = Blend {
+            a: Invert {
+                texture: red.clone(),
+            },
+            b: Multiply { a: red, b: gray },
+            factor: SolidColor::gray(0.5),
+        };
+        assert_eq!(graph.value(0.0, 0.0, &p), Vec3::new(0.25, 0.5, 0.5));
+
+        let mask = ExtractChannel {
+            texture: graph,
+            channel: Channel::Green,
+        };
+        assert_eq!(mask.alpha(0.0, 0.0, &p), 0.5);
+        assert_eq!(mask.value(0.0, 0.0, &p), Vec3::splat(0.5));
+    }
+}
@@ -3,7 +3,8 @@ use perlin_noise::PNG;
 
 /// Texture of noise similar to marble
 ///
-/// This texture uses internally a Perlin Noise Generator
+/// This texture uses internally a Perlin Noise Generator. See [Marble] for
+/// one with colors and more parameters.
 #[derive(Clone)]
 pub struct NoiseTexture {
     noise: PNG,
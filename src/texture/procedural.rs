@@ -0,0 +1,410 @@
+use super::*;
+use perlin_noise::PNG;
+
+/// Map from numbers to colors
+///
+/// Made of color stops at positions along [`0.0`, `1.0`], with the colors
+/// linearly interpolated between them. Values before the first stop or
+/// after the last one take the color of that stop.
+#[derive(Clone, Debug)]
+pub struct ColorRamp {
+    stops: Vec<(f64, Vec3)>,
+}
+
+impl ColorRamp {
+    /// Creates a new [ColorRamp] from its stops, as (position, color). The
+    /// stops don't need to be sorted. A ramp without stops is black.
+    pub fn new(mut stops: Vec<(f64, Vec3)>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// Creates a ramp from the color `a` at `0.0` to `b` at `1.0`
+    pub fn between(a: Vec3, b: Vec3) -> Self {
+        Self::new(vec![(0.0, a), (1.0, b)])
+    }
+
+    /// Creates a ramp from black to white
+    pub fn grayscale() -> Self {
+        Self::between(Vec3::zero(), Vec3::one())
+    }
+
+    /// Color of the ramp at `t`
+    pub fn sample(&self, t: f64) -> Vec3 {
+        let next = self.stops.partition_point(|(position, _)| *position <= t);
+        match (next.checked_sub(1), self.stops.get(next)) {
+            (Some(prev), Some((p1, c1))) => {
+                let (p0, c0) = self.stops[prev];
+                let f = (t - p0) / (p1 - p0);
+                c0 + (*c1 - c0) * f
+            }
+            (Some(prev), None) => self.stops[prev].1,
+            (None, Some((_, c1))) => *c1,
+            (None, None) => Vec3::zero(),
+        }
+    }
+}
+
+/// Texture of marble veins
+///
+/// Bands of sine waves along an axis, distorted by Perlin turbulence and
+/// colored with a [ColorRamp]. A configurable version of [NoiseTexture]:
+/// [Marble::new] with the same scale looks the same.
+#[derive(Clone)]
+pub struct Marble {
+    /// Generator of the noise
+    pub noise: PNG,
+    /// Direction across the veins
+    pub axis: Vec3,
+    /// Frequency of the veins along the axis
+    pub frequency: f64,
+    /// Frequency of the turbulence
+    pub scale: f64,
+    /// How much the turbulence distorts the veins
+    pub turbulence: f64,
+    pub ramp: ColorRamp,
+}
+
+impl Marble {
+    /// Creates a new gray [Marble] with veins of the given frequency
+    pub fn new(frequency: f64) -> Self {
+        Self {
+            noise: PNG::new(),
+            axis: Vec3::new(0.0, 0.0, 1.0),
+            frequency,
+            scale: 1.0,
+            turbulence: 10.0,
+            ramp: ColorRamp::grayscale(),
+        }
+    }
+}
+
+impl Texture for Marble {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let phase = self.frequency * p.dot(&self.axis.unit_vector())
+            + self.turbulence * self.noise.turbulence(&(*p * self.scale));
+        self.ramp.sample(0.5 * (1.0 + phase.sin()))
+    }
+}
+
+/// Texture of wood rings
+///
+/// Concentric rings around an axis, like the growth rings of a trunk,
+/// wobbled by Perlin noise. Each ring goes through the whole [ColorRamp],
+/// from `0.0` in its inside to `1.0` in its outside.
+#[derive(Clone)]
+pub struct Wood {
+    /// Generator of the noise
+    pub noise: PNG,
+    /// Point on the axis of the trunk
+    pub origin: Vec3,
+    /// Direction of the axis of the trunk
+    pub axis: Vec3,
+    /// Rings per unit of distance from the axis
+    pub rings: f64,
+    /// Frequency of the noise
+    pub scale: f64,
+    /// How much the noise distorts the rings, in rings
+    pub distortion: f64,
+    pub ramp: ColorRamp,
+}
+
+impl Wood {
+    /// Creates a new [Wood] with the given rings per unit, around the
+    /// vertical axis through the origin
+    pub fn new(rings: f64) -> Self {
+        Self {
+            noise: PNG::new(),
+            origin: Vec3::zero(),
+            axis: Vec3::new(0.0, 1.0, 0.0),
+            rings,
+            scale: 1.0,
+            distortion: 0.5,
+            ramp: ColorRamp::new(vec![
+                (0.0, Vec3::new(0.75, 0.52, 0.3)),
+                (0.7, Vec3::new(0.65, 0.42, 0.22)),
+                (1.0, Vec3::new(0.35, 0.2, 0.1)),
+            ]),
+        }
+    }
+}
+
+impl Texture for Wood {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let d = *p - self.origin;
+        let axis = self.axis.unit_vector();
+        let radius = (d - axis * d.dot(&axis)).length();
+        let rings = radius * self.rings + self.distortion * self.noise.noise(&(*p * self.scale));
+        self.ramp.sample(rings.rem_euclid(1.0))
+    }
+}
+
+/// Distance that a [Worley] texture shows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WorleyFeature {
+    /// Distance to the closest feature point. Round cells, dark at their
+    /// centers.
+    F1,
+    /// Distance to the second closest feature point
+    F2,
+    /// Difference between [F2](WorleyFeature::F2) and
+    /// [F1](WorleyFeature::F1). Dark lines at the borders of the cells, like
+    /// cracks or scales.
+    Edges,
+}
+
+/// Texture of cellular (Worley) noise
+///
+/// Space is split in cells of size `1 / scale`, each with a random feature
+/// point, and the texture is a distance to the closest ones, in cells.
+/// Gives cells, stones, scales or cracks.
+#[derive(Clone)]
+pub struct Worley {
+    /// Seed of the feature points
+    pub seed: u64,
+    /// Cells per unit
+    pub scale: f64,
+    pub feature: WorleyFeature,
+    pub ramp: ColorRamp,
+}
+
+impl Worley {
+    /// Creates a new gray [Worley] texture with the given cells per unit
+    pub fn new(scale: f64) -> Self {
+        Self {
+            seed: rand::random(),
+            scale,
+            feature: WorleyFeature::F1,
+            ramp: ColorRamp::grayscale(),
+        }
+    }
+
+    /// Feature point of a cell, in cell units
+    fn feature_point(&self, cell: [i64; 3]) -> Vec3 {
+        let mut h = self.seed;
+        for c in cell {
+            h = splitmix64(h ^ c as u64);
+        }
+        let mut point = Vec3::zero();
+        for (coord, c) in point.iter_mut().zip(cell) {
+            h = splitmix64(h);
+            *coord = c as f64 + (h >> 11) as f64 / (1u64 << 53) as f64;
+        }
+        point
+    }
+
+    /// Distances from `p` to the 2 closest feature points, in cell units
+    fn distances(&self, p: &Vec3) -> (f64, f64) {
+        let p = *p * self.scale;
+        let cell = [p.x(), p.y(), p.z()].map(|c| c.floor() as i64);
+        let (mut f1, mut f2) = (f64::INFINITY, f64::INFINITY);
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                for dk in -1..=1 {
+                    let point = self.feature_point([cell[0] + di, cell[1] + dj, cell[2] + dk]);
+                    let d = (point - p).length();
+                    if d < f1 {
+                        f2 = f1;
+                        f1 = d;
+                    } else if d < f2 {
+                        f2 = d;
+                    }
+                }
+            }
+        }
+        (f1, f2)
+    }
+}
+
+/// Mixes the bits of a number, for hashing
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+impl Texture for Worley {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let (f1, f2) = self.distances(p);
+        let t = match self.feature {
+            WorleyFeature::F1 => f1,
+            WorleyFeature::F2 => f2,
+            WorleyFeature::Edges => f2 - f1,
+        };
+        self.ramp.sample(t)
+    }
+}
+
+/// Texture of clouds
+///
+/// Fractional Brownian motion (fBm): layers of Perlin noise of growing
+/// frequency and shrinking amplitude. Its values are around `0.5`, mostly
+/// in [`0.0`, `1.0`], before the [ColorRamp].
+#[derive(Clone)]
+pub struct Clouds {
+    /// Generator of the noise
+    pub noise: PNG,
+    /// Frequency of the first layer
+    pub scale: f64,
+    /// Number of layers
+    pub octaves: usize,
+    /// Frequency of each layer relative to the previous one
+    pub lacunarity: f64,
+    /// Amplitude of each layer relative to the previous one
+    pub gain: f64,
+    pub ramp: ColorRamp,
+}
+
+impl Clouds {
+    /// Creates new gray [Clouds] with the given frequency
+    pub fn new(scale: f64) -> Self {
+        Self {
+            noise: PNG::new(),
+            scale,
+            octaves: 6,
+            lacunarity: 2.0,
+            gain: 0.5,
+            ramp: ColorRamp::grayscale(),
+        }
+    }
+}
+
+impl Texture for Clouds {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let fbm = self
+            .noise
+            .fbm(&(*p * self.scale), self.octaves, self.lacunarity, self.gain);
+        self.ramp.sample(0.5 + fbm)
+    }
+}
+
+/// Shape of a [Gradient]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientKind {
+    /// Changes along the line from the start to the end
+    Linear,
+    /// Changes with the distance to the start, reaching the end of the
+    /// ramp at the distance to the end
+    Spherical,
+}
+
+/// Texture of a gradient between 2 points in space
+#[derive(Clone)]
+pub struct Gradient {
+    /// Point at `0.0` in the [ColorRamp]
+    pub start: Vec3,
+    /// Point at `1.0` in the [ColorRamp]
+    pub end: Vec3,
+    pub kind: GradientKind,
+    pub ramp: ColorRamp,
+}
+
+impl Gradient {
+    /// Creates a linear [Gradient] from the color `a` at `start` to `b` at `end`
+    pub fn linear(start: Vec3, end: Vec3, a: Vec3, b: Vec3) -> Self {
+        Self {
+            start,
+            end,
+            kind: GradientKind::Linear,
+            ramp: ColorRamp::between(a, b),
+        }
+    }
+}
+
+impl Texture for Gradient {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let d = *p - self.start;
+        let span = self.end - self.start;
+        let t = match self.kind {
+            GradientKind::Linear => d.dot(&span) / span.length_squared(),
+            GradientKind::Spherical => d.length() / span.length(),
+        };
+        self.ramp.sample(t)
+    }
+}
+
+/// Texture of parallel stripes
+///
+/// Each period of the stripes has 2 parts: the first `ratio` of it takes
+/// the color at `1.0` in the [ColorRamp], and the rest the one at `0.0`.
+/// At a hit the stripes are averaged over the footprint of the ray, so
+/// they don't alias when far away.
+#[derive(Clone)]
+pub struct Stripes {
+    /// Direction across the stripes. Its length is the number of periods
+    /// per unit.
+    pub direction: Vec3,
+    /// Fraction of each period in the first part
+    pub ratio: f64,
+    pub ramp: ColorRamp,
+}
+
+impl Stripes {
+    /// Creates new [Stripes] of colors `a` and `b` across the given direction
+    pub fn new(direction: Vec3, a: Vec3, b: Vec3) -> Self {
+        Self {
+            direction,
+            ratio: 0.5,
+            ramp: ColorRamp::between(b, a),
+        }
+    }
+
+    /// Fraction of the first part of the stripes in [`s - width / 2`,
+    /// `s + width / 2`], with `s` in periods
+    fn coverage(&self, s: f64, width: f64) -> f64 {
+        // Integral of the first part from 0 to x
+        let integral = |x: f64| x.floor() * self.ratio + x.rem_euclid(1.0).min(self.ratio);
+        if width > 0.0 {
+            (integral(s + 0.5 * width) - integral(s - 0.5 * width)) / width
+        } else if s.rem_euclid(1.0) < self.ratio {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Texture for Stripes {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        self.ramp.sample(self.coverage(p.dot(&self.direction), 0.0))
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        let s = hit.point.dot(&self.direction);
+        let width = hit.footprint * self.direction.length();
+        self.ramp.sample(self.coverage(s, width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_ramp() {
+        let ramp = ColorRamp::new(vec![
+            (1.0, Vec3::new(0.0, 0.0, 1.0)),
+            (0.0, Vec3::new(1.0, 0.0, 0.0)),
+            (0.5, Vec3::new(0.0, 1.0, 0.0)),
+        ]);
+        assert_eq!(ramp.sample(-1.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(ramp.sample(0.25), Vec3::new(0.5, 0.5, 0.0));
+        assert_eq!(ramp.sample(0.5), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(ramp.sample(2.0), Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn worley_distances() {
+        let worley = Worley::new(1.0);
+        let point = worley.feature_point([2, -1, 0]);
+        assert!(point.x() >= 2.0 && point.x() < 3.0);
+        assert!(point.y() >= -1.0 && point.y() < 0.0);
+        // Same point every time
+        assert_eq!(point, worley.feature_point([2, -1, 0]));
+
+        let (f1, f2) = worley.distances(&point);
+        assert_eq!(f1, 0.0);
+        assert!(f2 > 0.0);
+    }
+}
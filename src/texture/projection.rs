@@ -0,0 +1,276 @@
+use super::*;
+use crate::object::get_sphere_uv;
+use std::f64::consts::TAU;
+
+/// Space where a projection places its plane
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProjectionSpace {
+    /// The plane stays in place while objects move
+    World,
+    /// The plane moves with the object, using the point of the hit before
+    /// the [Translate](crate::object::Translate) and
+    /// [RotateY](crate::object::RotateY) that place it (its
+    /// [object_point](HitRecord::object_point))
+    Object,
+}
+
+/// Planar projection of a texture
+///
+/// Ignores the coordinates of the surface and finds new ones projecting the
+/// hit point onto a plane, like a slide projector. The plane is in world or
+/// object [space](PlanarProjection::space). Lookups without a hit (like
+/// [value](Texture::value)) only know the point, so they use world space.
+#[derive(Clone)]
+pub struct PlanarProjection<T: Texture> {
+    pub texture: T,
+    /// Space of the plane
+    pub space: ProjectionSpace,
+    /// Point of the plane where the coordinates are (`0.0`, `0.0`)
+    pub origin: Vec3,
+    /// Direction where `u` grows. Its length is the size of the texture along it.
+    pub u_axis: Vec3,
+    /// Direction where `v` grows. Its length is the size of the texture along it.
+    pub v_axis: Vec3,
+}
+
+impl<T: Texture> PlanarProjection<T> {
+    /// Returns the coordinates of the projection of `p`
+    fn project(&self, p: &Vec3) -> (f64, f64) {
+        let d = *p - self.origin;
+        (
+            d.dot(&self.u_axis) / self.u_axis.length_squared(),
+            d.dot(&self.v_axis) / self.v_axis.length_squared(),
+        )
+    }
+}
+
+impl<T: Texture> Texture for PlanarProjection<T> {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let (u, v) = self.project(p);
+        self.texture.value(u, v, p)
+    }
+
+    fn alpha(&self, _u: f64, _v: f64, p: &Vec3) -> f64 {
+        let (u, v) = self.project(p);
+        self.texture.alpha(u, v, p)
+    }
+
+    // Without a hit the footprint in world units is unknown, so `width` is
+    // kept as a width in the projected coordinates
+    fn filtered(&self, _u: f64, _v: f64, p: &Vec3, width: f64) -> Vec3 {
+        let (u, v) = self.project(p);
+        self.texture.filtered(u, v, p, width)
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        let (u, v) = match self.space {
+            ProjectionSpace::World => self.project(&hit.point),
+            ProjectionSpace::Object => self.project(&hit.object_point),
+        };
+        let size = self.u_axis.length().min(self.v_axis.length());
+        self.texture
+            .filtered(u, v, &hit.point, hit.footprint / size)
+    }
+}
+
+/// Box (triplanar) projection of a texture
+///
+/// Projects the texture along the 3 axes, like onto the faces of a box, and
+/// blends the projections by how much the surface faces each axis. Textures
+/// any surface without stretching, good for rocks or terrain.
+///
+/// The blend needs the normal of the surface, so lookups without a hit
+/// (like [value](Texture::value)) blend the 3 projections equally.
+#[derive(Clone)]
+pub struct TriplanarProjection<T: Texture> {
+    pub texture: T,
+    /// Point where the coordinates of the 3 projections are (`0.0`, `0.0`)
+    pub origin: Vec3,
+    /// Size of the texture in world units
+    pub size: f64,
+    /// How fast a projection fades as the surface turns from its axis.
+    /// `1.0` is a soft blend and higher values make it sharper.
+    pub sharpness: f64,
+}
+
+impl<T: Texture> TriplanarProjection<T> {
+    /// Creates a new [TriplanarProjection] of a texture with the given size
+    pub fn new(texture: T, size: f64) -> Self {
+        Self {
+            texture,
+            origin: Vec3::zero(),
+            size,
+            sharpness: 4.0,
+        }
+    }
+
+    /// Returns the weight of the projection along each axis
+    fn weights(&self, normal: &Vec3) -> Vec3 {
+        let weights = normal.map(|c| c.abs().powf(self.sharpness));
+        let total = weights.x() + weights.y() + weights.z();
+        if total > 0.0 {
+            weights / total
+        } else {
+            Vec3::splat(1.0 / 3.0)
+        }
+    }
+
+    /// Blends the 3 projections of `p`, each one looked up with `lookup`
+    fn blend<F: Fn(f64, f64) -> Vec3>(&self, p: &Vec3, weights: &Vec3, lookup: F) -> Vec3 {
+        let d = (*p - self.origin) / self.size;
+        let planes = [(d.z(), d.y()), (d.x(), d.z()), (d.x(), d.y())];
+        planes
+            .iter()
+            .zip(weights.iter())
+            .filter(|(_, weight)| **weight > 0.0)
+            .map(|(&(u, v), weight)| *weight * lookup(u, v))
+            .sum()
+    }
+}
+
+impl<T: Texture> Texture for TriplanarProjection<T> {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let weights = Vec3::splat(1.0 / 3.0);
+        self.blend(p, &weights, |u, v| self.texture.value(u, v, p))
+    }
+
+    fn alpha(&self, _u: f64, _v: f64, p: &Vec3) -> f64 {
+        let weights = Vec3::splat(1.0 / 3.0);
+        self.blend(p, &weights, |u, v| Vec3::splat(self.texture.alpha(u, v, p)))
+            .x()
+    }
+
+    fn filtered(&self, _u: f64, _v: f64, p: &Vec3, width: f64) -> Vec3 {
+        let weights = Vec3::splat(1.0 / 3.0);
+        self.blend(p, &weights, |u, v| self.texture.filtered(u, v, p, width))
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        let weights = self.weights(&hit.geometric_normal);
+        let width = hit.footprint / self.size;
+        self.blend(&hit.point, &weights, |u, v| {
+            self.texture.filtered(u, v, &hit.point, width)
+        })
+    }
+}
+
+/// Spherical projection of a texture
+///
+/// Ignores the coordinates of the surface and finds new ones from the
+/// direction of the hit point seen from a center, as a sphere around it
+/// would have. Good for environment-like images or round objects without
+/// coordinates of their own.
+#[derive(Clone)]
+pub struct SphericalProjection<T: Texture> {
+    pub texture: T,
+    /// Center of the projection
+    pub center: Vec3,
+}
+
+impl<T: Texture> SphericalProjection<T> {
+    /// Returns the coordinates of the projection of `p`
+    fn project(&self, p: &Vec3) -> (f64, f64) {
+        get_sphere_uv(&(*p - self.center).unit_vector())
+    }
+}
+
+impl<T: Texture> Texture for SphericalProjection<T> {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let (u, v) = self.project(p);
+        self.texture.value(u, v, p)
+    }
+
+    fn alpha(&self, _u: f64, _v: f64, p: &Vec3) -> f64 {
+        let (u, v) = self.project(p);
+        self.texture.alpha(u, v, p)
+    }
+
+    fn filtered(&self, _u: f64, _v: f64, p: &Vec3, width: f64) -> Vec3 {
+        let (u, v) = self.project(p);
+        self.texture.filtered(u, v, p, width)
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        let (u, v) = self.project(&hit.point);
+        // Width of the footprint as an angle, in turns
+        let distance = (hit.point - self.center).length();
+        let width = if distance > 0.0 {
+            hit.footprint / (TAU * distance)
+        } else {
+            0.0
+        };
+        self.texture.filtered(u, v, &hit.point, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::Hittable;
+    use crate::material::Lambertian;
+    use crate::object::{Sphere, Translate};
+    use crate::ray::Ray;
+
+    /// Texture whose color is its coordinates
+    struct Coords;
+
+    impl Texture for Coords {
+        fn value(&self, u: f64, v: f64, _p: &Vec3) -> Vec3 {
+            Vec3::new(u, v, 0.0)
+        }
+    }
+
+    #[test]
+    fn planar_projection() {
+        let projection = PlanarProjection {
+            texture: Coords,
+            space: ProjectionSpace::World,
+            origin: Vec3::new(1.0, 0.0, 0.0),
+            u_axis: Vec3::new(2.0, 0.0, 0.0),
+            v_axis: Vec3::new(0.0, 0.0, 4.0),
+        };
+        let p = Vec3::new(2.0, 5.0, 2.0);
+        let expected = Vec3::new(0.5, 0.5, 0.0);
+        assert_eq!(projection.value(0.9, 0.1, &p), expected);
+        assert_eq!(projection.filtered(0.9, 0.1, &p, 0.01), expected);
+    }
+
+    #[test]
+    fn object_space_moves_with_the_object() {
+        let projection = PlanarProjection {
+            texture: Coords,
+            space: ProjectionSpace::Object,
+            origin: Vec3::zero(),
+            u_axis: Vec3::new(1.0, 0.0, 0.0),
+            v_axis: Vec3::new(0.0, 1.0, 0.0),
+        };
+        let sphere = Translate::new(
+            Sphere {
+                center: Vec3::zero(),
+                radius: 1.0,
+                material: Lambertian::from_color(Vec3::one()),
+            },
+            Vec3::new(10.0, 0.0, 0.0),
+        );
+        let r = Ray::new(Vec3::new(10.5, 0.25, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = sphere.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!((projection.value_at(&hit) - Vec3::new(0.5, 0.25, 0.0)).length() < 1e-9);
+
+        let world = PlanarProjection {
+            space: ProjectionSpace::World,
+            ..projection
+        };
+        assert!((world.value_at(&hit) - Vec3::new(10.5, 0.25, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn spherical_projection_ignores_the_distance() {
+        let projection = SphericalProjection {
+            texture: Coords,
+            center: Vec3::new(0.0, 1.0, 0.0),
+        };
+        let near = projection.value(0.0, 0.0, &Vec3::new(1.0, 2.0, 0.0));
+        let far = projection.filtered(0.0, 0.0, &Vec3::new(3.0, 4.0, 0.0), 0.01);
+        assert!((near - far).length() < 1e-9);
+    }
+}
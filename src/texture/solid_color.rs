@@ -15,6 +15,14 @@ impl SolidColor {
             color: Vec3::new(red, green, blue),
         }
     }
+
+    /// Creates a new [SolidColor] texture with the same `value` in every
+    /// channel. Useful for parameters that are a single number.
+    pub fn gray(value: f64) -> Self {
+        Self {
+            color: Vec3::splat(value),
+        }
+    }
 }
 
 impl Texture for SolidColor {
@@ -0,0 +1,138 @@
+use super::*;
+
+/// Transformation of the surface coordinates of a texture
+///
+/// Scales, rotates and offsets (in that order) the coordinates (`u`, `v`)
+/// before looking up the wrapped texture. A scale of `4.0` repeats the
+/// texture 4 times if it tiles, like an [ImageTexture] with
+/// [WrapMode::Repeat].
+#[derive(Clone)]
+pub struct UvTransform<T: Texture> {
+    pub texture: T,
+    /// Scale along `u` and `v`
+    pub scale: (f64, f64),
+    /// Rotation in degrees, counterclockwise
+    pub rotation: f64,
+    /// Offset added at the end
+    pub offset: (f64, f64),
+}
+
+impl<T: Texture> UvTransform<T> {
+    /// Wraps a texture without changing its coordinates
+    pub fn new(texture: T) -> Self {
+        Self {
+            texture,
+            scale: (1.0, 1.0),
+            rotation: 0.0,
+            offset: (0.0, 0.0),
+        }
+    }
+
+    /// Returns the transformed coordinates
+    fn transform(&self, u: f64, v: f64) -> (f64, f64) {
+        let (u, v) = (u * self.scale.0, v * self.scale.1);
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        (
+            cos * u - sin * v + self.offset.0,
+            sin * u + cos * v + self.offset.1,
+        )
+    }
+
+    /// Returns a width in the original coordinates in the transformed ones
+    fn transform_width(&self, width: f64) -> f64 {
+        width * self.scale.0.abs().max(self.scale.1.abs())
+    }
+}
+
+impl<T: Texture> Texture for UvTransform<T> {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        let (u, v) = self.transform(u, v);
+        self.texture.value(u, v, p)
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: &Vec3) -> f64 {
+        let (u, v) = self.transform(u, v);
+        self.texture.alpha(u, v, p)
+    }
+
+    fn filtered(&self, u: f64, v: f64, p: &Vec3, width: f64) -> Vec3 {
+        let (u, v) = self.transform(u, v);
+        self.texture.filtered(u, v, p, self.transform_width(width))
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Vec3 {
+        let (u, v) = self.transform(hit.u, hit.v);
+        // Shorter tangents widen the footprint as transform_width does
+        let scale = self.transform_width(1.0);
+        let hit = HitRecord {
+            u,
+            v,
+            dpdu: hit.dpdu / scale,
+            dpdv: hit.dpdv / scale,
+            ..hit.clone()
+        };
+        self.texture.value_at(&hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::testing::hit_from;
+    use crate::material::Lambertian;
+
+    /// Texture whose color is its coordinates
+    struct Coords;
+
+    impl Texture for Coords {
+        fn value(&self, u: f64, v: f64, _p: &Vec3) -> Vec3 {
+            Vec3::new(u, v, 0.0)
+        }
+    }
+
+    #[test]
+    fn rotation_after_scale() {
+        let transform = UvTransform {
+            texture: Coords,
+            scale: (2.0, 1.0),
+            rotation: 90.0,
+            offset: (1.0, 0.0),
+        };
+        let p = Vec3::zero();
+        // (0.25, 0.5) scales to (0.5, 0.5) and rotates to (-0.5, 0.5)
+        let expected = Vec3::new(0.5, 0.5, 0.0);
+        assert!((transform.value(0.25, 0.5, &p) - expected).length() < 1e-9);
+        assert!((transform.filtered(0.25, 0.5, &p, 0.01) - expected).length() < 1e-9);
+        assert_eq!(transform.transform_width(0.01), 0.02);
+    }
+
+    /// Texture whose color at a hit is its coordinates and footprint,
+    /// and black elsewhere
+    struct HitCoords;
+
+    impl Texture for HitCoords {
+        fn value(&self, _u: f64, _v: f64, _p: &Vec3) -> Vec3 {
+            Vec3::zero()
+        }
+
+        fn value_at(&self, hit: &HitRecord) -> Vec3 {
+            Vec3::new(hit.u, hit.v, hit.uv_footprint())
+        }
+    }
+
+    #[test]
+    fn value_at_uses_the_transformed_coords() {
+        let transform = UvTransform {
+            scale: (2.0, 1.0),
+            offset: (0.0, 0.5),
+            ..UvTransform::new(HitCoords)
+        };
+        let material = Lambertian::from_color(Vec3::one());
+        let (_, mut hit) = hit_from(Vec3::new(0.0, 0.0, 1.0), &material);
+        hit.u = 0.25;
+        hit.v = 0.25;
+        hit.footprint = 0.1;
+        let expected = Vec3::new(0.5, 0.75, 0.2);
+        assert!((transform.value_at(&hit) - expected).length() < 1e-9);
+    }
+}
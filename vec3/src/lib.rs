@@ -111,6 +111,29 @@ impl Vec3 {
         }
     }
 
+    /// Creates a random unit vector in the hemisphere around the `z` axis, with a
+    /// probability density proportional to the cosine of its angle with it.
+    #[must_use]
+    pub fn random_cosine_direction() -> Self {
+        use std::f64::consts::TAU;
+        let r1: f64 = rand::random();
+        let r2: f64 = rand::random();
+        let z = (1.0 - r2).sqrt();
+
+        let phi = TAU * r1;
+        let x = phi.cos() * r2.sqrt();
+        let y = phi.sin() * r2.sqrt();
+
+        Self::new(x, y, z)
+    }
+
+    /// Returns the relative luminance of the vector interpreted as a linear
+    /// RGB color (Rec. 709 weights).
+    #[inline]
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x() + 0.7152 * self.y() + 0.0722 * self.z()
+    }
+
     pub fn near_zero(&self) -> bool {
         (self.v[0].abs() < FLOAT_CMP_ERROR)
             && (self.v[1].abs() < FLOAT_CMP_ERROR)
@@ -203,12 +226,12 @@ impl Vec3 {
     }
 
     #[inline]
-    pub fn iter(&self) -> std::slice::Iter<f64> {
+    pub fn iter(&self) -> std::slice::Iter<'_, f64> {
         self.v.iter()
     }
 
     #[inline]
-    pub fn iter_mut(&mut self) -> std::slice::IterMut<f64> {
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, f64> {
         self.v.iter_mut()
     }
 
@@ -590,7 +613,7 @@ mod tests {
     fn vector_scale_operators() {
         let original = Vec3 { v: [1.0, 2.0, 3.0] };
         let r = Vec3 { v: [2.0, 4.0, 6.0] };
-        let v1 = original.clone() * 2.0;
+        let v1 = original * 2.0;
         assert_eq_vec3!(v1, r);
         let v2 = v1 / 2.0;
         assert_eq_vec3!(v2, original);
@@ -611,7 +634,7 @@ mod tests {
     #[test]
     fn index_operator() {
         let mut v = Vec3 { v: [1.0, 2.0, 3.0] };
-        let v_clone = v.clone();
+        let v_clone = v;
         assert_eq!(v.x(), v[0]);
         assert_eq!(v.y(), v[1]);
         assert_eq!(v.z(), v[2]);